@@ -0,0 +1,3 @@
+-- price breaks per part, a JSON array of {"min_quantity", "max_quantity", "price"} ordered by quantity,
+-- loaded next to the scraped price, see "Price tiers" in the README
+ALTER TABLE parts ADD COLUMN IF NOT EXISTS price_tiers jsonb;
@@ -0,0 +1,17 @@
+-- power rating in watt and manufacturer tolerance in percent, parsed from the description
+-- e.g. "100mW Thick Film Resistors 75V ±1% ±100ppm/℃ 10kΩ 0603 Chip Resistor - Surface Mount ROHS"
+ALTER TABLE parts ADD COLUMN IF NOT EXISTS power float8 GENERATED ALWAYS AS (
+    CASE
+        WHEN description ~ '(^|\s)\d+/\d+W(\s|$)' THEN
+            substring(description from '(?:^|\s)(\d+)/\d+W(?:\s|$)')::float8
+            / substring(description from '(?:^|\s)\d+/(\d+)W(?:\s|$)')::float8
+        WHEN description ~ '(^|\s)\d+(\.\d+)?mW(\s|$)' THEN
+            substring(description from '(?:^|\s)(\d+(?:\.\d+)?)mW(?:\s|$)')::float8 / 1000
+        WHEN description ~ '(^|\s)\d+(\.\d+)?W(\s|$)' THEN
+            substring(description from '(?:^|\s)(\d+(?:\.\d+)?)W(?:\s|$)')::float8
+    END
+) STORED;
+
+ALTER TABLE parts ADD COLUMN IF NOT EXISTS tolerance float8 GENERATED ALWAYS AS (
+    substring(description from '±(\d+(?:\.\d+)?)%')::float8
+) STORED;
@@ -0,0 +1,26 @@
+-- saturation current of inductors in ampere, parsed from the description where it follows
+-- the rated current, e.g. "4.7uH ±20% 2.1A 2.5A 62mΩ SMD,4x4mm Power Inductors ROHS"
+ALTER TABLE parts ADD COLUMN IF NOT EXISTS saturation_current float8 GENERATED ALWAYS AS (
+    CASE
+        WHEN description ~ 'Inductors' THEN
+            substring(description from '(?:^|\s)\d+(?:\.\d+)?m?A\s(\d+(?:\.\d+)?)m?A(?:\s|$)')::float8
+            * CASE substring(description from '(?:^|\s)\d+(?:\.\d+)?m?A\s\d+(?:\.\d+)?(m?)A(?:\s|$)')
+                WHEN 'm' THEN 1e-3
+                ELSE 1
+            END
+    END
+) STORED;
+
+-- DC resistance of inductors in ohm, parsed from the description
+-- e.g. "4.7uH ±20% 2.1A 62mΩ SMD,4x4mm Power Inductors ROHS"
+ALTER TABLE parts ADD COLUMN IF NOT EXISTS dcr float8 GENERATED ALWAYS AS (
+    CASE
+        WHEN description ~ 'Inductors' THEN
+            substring(description from '(?:^|\s)(\d+(?:\.\d+)?)[mk]?Ω(?:\s|$)')::float8
+            * CASE substring(description from '(?:^|\s)\d+(?:\.\d+)?([mk])Ω(?:\s|$)')
+                WHEN 'm' THEN 1e-3
+                WHEN 'k' THEN 1e3
+                ELSE 1
+            END
+    END
+) STORED;
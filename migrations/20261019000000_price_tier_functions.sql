@@ -0,0 +1,15 @@
+-- the price break of a part at an order quantity, the tier with the largest minimum quantity
+-- not above the order quantity, a NULL quantity is a single part
+CREATE OR REPLACE FUNCTION price_tier(price_tiers jsonb, quantity int8) RETURNS jsonb
+LANGUAGE sql IMMUTABLE PARALLEL SAFE AS $$
+    SELECT tier FROM jsonb_array_elements(price_tiers) tier
+    WHERE (tier->>'min_quantity')::int8 <= COALESCE(quantity, 1)
+    ORDER BY (tier->>'min_quantity')::int8 DESC
+    LIMIT 1
+$$;
+
+-- unit price at an order quantity, the single stored price when the part has no tiers
+CREATE OR REPLACE FUNCTION unit_price(price_tiers jsonb, price float8, quantity int8) RETURNS float8
+LANGUAGE sql IMMUTABLE PARALLEL SAFE AS $$
+    SELECT COALESCE((price_tier(price_tiers, quantity)->>'price')::float8, price)
+$$;
@@ -0,0 +1,7 @@
+-- full text search document of a part for the keyword search, the simple configuration
+-- keeps part numbers and units such as 10kΩ intact
+ALTER TABLE parts ADD COLUMN IF NOT EXISTS search_document tsvector GENERATED ALWAYS AS (
+    to_tsvector('simple', coalesce(description, '') || ' ' || coalesce(manufacturer, '') || ' ' || coalesce(mfr, ''))
+) STORED;
+
+CREATE INDEX IF NOT EXISTS parts_search_document_idx ON parts USING GIN (search_document);
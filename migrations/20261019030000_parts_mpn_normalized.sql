@@ -0,0 +1,11 @@
+-- trigram indexes for part number patterns and the edit distance of fuzzy part number matches
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+CREATE EXTENSION IF NOT EXISTS fuzzystrmatch;
+
+-- part number in upper case without whitespace, the form the part number searches match on
+ALTER TABLE parts ADD COLUMN IF NOT EXISTS mpn_normalized text GENERATED ALWAYS AS (
+    upper(regexp_replace(coalesce(mfr, ''), '\s', '', 'g'))
+) STORED;
+
+-- the trigram index serves exact matches, prefix patterns and the regular expressions of fuzzy matches
+CREATE INDEX IF NOT EXISTS parts_mpn_normalized_idx ON parts USING GIN (mpn_normalized gin_trgm_ops);
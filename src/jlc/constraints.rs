@@ -0,0 +1,410 @@
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgArguments;
+use sqlx::{Arguments, PgPool, Row};
+use utoipa::ToSchema;
+
+use crate::jlc::error::PartError;
+use crate::jlc::package::{chip_size_index, package_aliases, CHIP_SIZES};
+use crate::jlc::quantity::Unit;
+
+/// The stored value a search matches on
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueColumn {
+    #[default]
+    Resistance,
+    Capacitance,
+    Inductance,
+}
+
+impl ValueColumn {
+    /// The column storing values of the given unit
+    pub fn of_unit(unit: Unit) -> Self {
+        match unit {
+            Unit::Ohm => ValueColumn::Resistance,
+            Unit::Farad => ValueColumn::Capacitance,
+            Unit::Henry => ValueColumn::Inductance,
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ValueColumn::Resistance => "resistance",
+            ValueColumn::Capacitance => "capacitance",
+            ValueColumn::Inductance => "inductance",
+        }
+    }
+
+    /// Category and subcategory name of the parts searched by this value
+    pub fn category(&self) -> (&'static str, &'static str) {
+        match self {
+            ValueColumn::Resistance => ("Resistors", "Chip Resistor - Surface Mount"),
+            ValueColumn::Capacitance => {
+                ("Capacitors", "Multilayer Ceramic Capacitors MLCC - SMD/SMT")
+            }
+            ValueColumn::Inductance => ("Inductors/Coils/Transformers", "Inductors (SMD)"),
+        }
+    }
+
+    /// Id of the category of the parts searched by this value
+    pub async fn category_id(&self, pool: &PgPool) -> Result<i32, sqlx::Error> {
+        let (name, subcategory_name) = self.category();
+        let category_id: (i32,) =
+            sqlx::query_as("SELECT id FROM categories WHERE name = $1 and subcategory_name = $2")
+                .bind(name)
+                .bind(subcategory_name)
+                .fetch_one(pool)
+                .await?;
+        Ok(category_id.0)
+    }
+}
+
+/// The filters of a value search, a `None` filter accepts any part
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SearchConstraints {
+    pub category_id: i32,
+    pub column: ValueColumn,
+    /// Requested nominal value and value window, in the unit of the parts table
+    pub nominal: f64,
+    pub min_value: f64,
+    pub max_value: f64,
+    pub packages: Option<Vec<String>>,
+    pub dielectrics: Option<Vec<String>>,
+    pub min_voltage: Option<f64>,
+    pub max_voltage: Option<f64>,
+    pub min_power: Option<f64>,
+    pub max_part_tolerance: Option<f64>,
+    pub min_current: Option<f64>,
+    pub min_saturation_current: Option<f64>,
+    pub max_dcr: Option<f64>,
+    pub min_stock: Option<i64>,
+    pub basic_only: Option<bool>,
+    pub preferred_parts: Vec<String>,
+}
+
+/// Number of parts left after a constraint and every constraint before it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct StageCount {
+    pub constraint: String,
+    pub remaining: i64,
+}
+
+/// The best part that matches when a single constraint is relaxed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct Relaxation {
+    /// The relaxed constraint: `value` for the closest in-stock value outside the tolerance,
+    /// `package` for the same value in an adjacent chip package and `basic_only` for an extended part
+    pub constraint: String,
+    pub lcsc: String,
+    pub mfr: Option<String>,
+    pub package: Option<String>,
+    pub basic: bool,
+    pub stock: i64,
+    /// Stored value of the part, in the unit of the parts table
+    pub value: Option<f64>,
+}
+
+/// Why a search does or does not match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SearchDiagnostics {
+    /// Parts left after each active constraint, starting with every part of the category
+    pub stages: Vec<StageCount>,
+    /// The constraint after which no part is left
+    pub eliminating_constraint: Option<String>,
+    /// Nearest relaxations of the request, only given when no part is left
+    pub suggestions: Vec<Relaxation>,
+}
+
+/// The constraints after the category, in the order they are applied, with the condition
+/// that a part has to meet. A NULL parameter disables the condition.
+const STAGES: [(&str, &str); 12] = [
+    ("value", "{value} between $2 and $3"),
+    ("package", "($4::text[] IS NULL OR package = ANY($4))"),
+    ("dielectric", "($5::text[] IS NULL OR dielectric = ANY($5))"),
+    ("min_voltage", "($6::float8 IS NULL OR voltage >= $6)"),
+    ("max_voltage", "($7::float8 IS NULL OR voltage <= $7)"),
+    ("power", "($8::float8 IS NULL OR power >= $8)"),
+    ("part_tolerance", "($9::float8 IS NULL OR tolerance <= $9)"),
+    ("current", "($10::float8 IS NULL OR current >= $10)"),
+    (
+        "saturation_current",
+        "($11::float8 IS NULL OR saturation_current >= $11)",
+    ),
+    ("dcr", "($12::float8 IS NULL OR dcr <= $12)"),
+    ("stock", "($13::int8 IS NULL OR stock >= $13)"),
+    (
+        "basic_only",
+        "($14::bool IS NOT TRUE OR basic OR lcsc = ANY($15))",
+    ),
+];
+
+impl SearchConstraints {
+    /// Whether each of `STAGES` filters anything
+    fn active(&self) -> [bool; 12] {
+        [
+            true,
+            self.packages.is_some(),
+            self.dielectrics.is_some(),
+            self.min_voltage.is_some(),
+            self.max_voltage.is_some(),
+            self.min_power.is_some(),
+            self.max_part_tolerance.is_some(),
+            self.min_current.is_some(),
+            self.min_saturation_current.is_some(),
+            self.max_dcr.is_some(),
+            self.min_stock.is_some(),
+            self.basic_only == Some(true),
+        ]
+    }
+
+    /// The conditions of `STAGES` up to and including `count`, combined
+    fn conditions(&self, count: usize) -> String {
+        self.conditions_except(&STAGES[..count], "")
+    }
+
+    /// The conditions of the given stages except the relaxed one, combined
+    fn conditions_except(&self, stages: &[(&str, &str)], relaxed: &str) -> String {
+        stages
+            .iter()
+            .filter(|(constraint, _)| *constraint != relaxed)
+            .map(|(_, condition)| condition.replace("{value}", self.column.name()))
+            .collect::<Vec<_>>()
+            .join(" and ")
+    }
+
+    /// Aliases of the chip packages next to the requested ones, `None` without chip packages
+    fn adjacent_packages(&self) -> Option<Vec<String>> {
+        let mut indices: Vec<usize> = self
+            .packages
+            .iter()
+            .flatten()
+            .filter_map(|package| chip_size_index(package))
+            .collect();
+        indices.sort();
+        indices.dedup();
+        let adjacent: Vec<String> = indices
+            .iter()
+            .flat_map(|index| [index.checked_sub(1), Some(index + 1)])
+            .flatten()
+            .filter(|index| *index < CHIP_SIZES.len() && !indices.contains(index))
+            .flat_map(|index| package_aliases(CHIP_SIZES[index].imperial))
+            .collect();
+        (!adjacent.is_empty()).then_some(adjacent)
+    }
+
+    /// The parts of the category meeting every constraint, a FROM clause over
+    /// the parts table `p` with the parameters of `arguments`
+    pub(crate) fn source(&self) -> String {
+        format!(
+            "FROM parts p WHERE category_id = $1 and {}",
+            self.conditions(STAGES.len())
+        )
+    }
+
+    /// The parameters of the queries, see `STAGES`. $16 is the nominal value
+    /// and $17 the aliases of the adjacent packages.
+    pub(crate) fn arguments(&self) -> Result<PgArguments, sqlx::Error> {
+        let mut arguments = PgArguments::default();
+        arguments
+            .add(self.category_id)
+            .map_err(sqlx::Error::Encode)?;
+        arguments.add(self.min_value).map_err(sqlx::Error::Encode)?;
+        arguments.add(self.max_value).map_err(sqlx::Error::Encode)?;
+        arguments
+            .add(self.packages.clone())
+            .map_err(sqlx::Error::Encode)?;
+        arguments
+            .add(self.dielectrics.clone())
+            .map_err(sqlx::Error::Encode)?;
+        arguments
+            .add(self.min_voltage)
+            .map_err(sqlx::Error::Encode)?;
+        arguments
+            .add(self.max_voltage)
+            .map_err(sqlx::Error::Encode)?;
+        arguments.add(self.min_power).map_err(sqlx::Error::Encode)?;
+        arguments
+            .add(self.max_part_tolerance)
+            .map_err(sqlx::Error::Encode)?;
+        arguments
+            .add(self.min_current)
+            .map_err(sqlx::Error::Encode)?;
+        arguments
+            .add(self.min_saturation_current)
+            .map_err(sqlx::Error::Encode)?;
+        arguments.add(self.max_dcr).map_err(sqlx::Error::Encode)?;
+        arguments.add(self.min_stock).map_err(sqlx::Error::Encode)?;
+        arguments
+            .add(self.basic_only)
+            .map_err(sqlx::Error::Encode)?;
+        arguments
+            .add(self.preferred_parts.clone())
+            .map_err(sqlx::Error::Encode)?;
+        arguments.add(self.nominal).map_err(sqlx::Error::Encode)?;
+        arguments
+            .add(self.adjacent_packages())
+            .map_err(sqlx::Error::Encode)?;
+        Ok(arguments)
+    }
+
+    /// Count the parts left after each active constraint, starting with every part of the category
+    pub async fn count_stages(&self, pool: &PgPool) -> Result<Vec<StageCount>, sqlx::Error> {
+        let counts: Vec<String> = std::iter::once("count(*)".to_string())
+            .chain(
+                (1..=STAGES.len())
+                    .map(|count| format!("count(*) FILTER (WHERE {})", self.conditions(count))),
+            )
+            .collect();
+        let query = format!(
+            "SELECT {} FROM parts WHERE category_id = $1",
+            counts.join(", ")
+        );
+        let row = sqlx::query_with(&query, self.arguments()?)
+            .fetch_one(pool)
+            .await?;
+
+        let mut stages = vec![StageCount {
+            constraint: "category".to_string(),
+            remaining: row.try_get(0)?,
+        }];
+        for (index, ((constraint, _), active)) in STAGES.iter().zip(self.active()).enumerate() {
+            if active {
+                stages.push(StageCount {
+                    constraint: constraint.to_string(),
+                    remaining: row.try_get(index + 1)?,
+                });
+            }
+        }
+        Ok(stages)
+    }
+
+    /// The best part matching every constraint except the relaxed one and the extra condition
+    async fn relaxation(
+        &self,
+        pool: &PgPool,
+        relaxed: &str,
+        condition: &str,
+        order: &str,
+    ) -> Result<Option<Relaxation>, sqlx::Error> {
+        let column = self.column.name();
+        let query = format!(
+            r#"SELECT '{relaxed}' as "constraint", lcsc, mfr, package, basic, stock::int8 as stock, {column} as value FROM parts WHERE category_id = $1 and {condition} and {} ORDER BY {order}, lcsc LIMIT 1"#,
+            self.conditions_except(&STAGES, relaxed),
+        );
+        sqlx::query_as_with(&query, self.arguments()?)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// The nearest relaxations of the request: the closest in-stock value outside the tolerance,
+    /// the same value in an adjacent chip package and an extended part when basic_only is requested
+    pub async fn suggestions(&self, pool: &PgPool) -> Result<Vec<Relaxation>, sqlx::Error> {
+        let column = self.column.name();
+        let mut suggestions = Vec::new();
+        suggestions.extend(
+            self.relaxation(
+                pool,
+                "value",
+                &format!("{column} IS NOT NULL and NOT ({column} between $2 and $3) and stock > 0"),
+                &format!("abs({column} - $16), basic DESC, stock DESC"),
+            )
+            .await?,
+        );
+        if self.adjacent_packages().is_some() {
+            suggestions.extend(
+                self.relaxation(
+                    pool,
+                    "package",
+                    "package = ANY($17)",
+                    &format!("basic DESC, abs({column} - $16), stock DESC"),
+                )
+                .await?,
+            );
+        }
+        if self.basic_only == Some(true) {
+            suggestions.extend(
+                self.relaxation(
+                    pool,
+                    "basic_only",
+                    "NOT (basic OR lcsc = ANY($15))",
+                    &format!("abs({column} - $16), stock DESC"),
+                )
+                .await?,
+            );
+        }
+        Ok(suggestions)
+    }
+
+    /// Count the parts left after each constraint and, when none is left,
+    /// suggest the nearest relaxations
+    pub async fn diagnose(&self, pool: &PgPool) -> Result<SearchDiagnostics, sqlx::Error> {
+        let stages = self.count_stages(pool).await?;
+        let eliminating_constraint = eliminating_constraint(&stages).map(str::to_string);
+        let suggestions = match eliminating_constraint {
+            Some(_) => self.suggestions(pool).await?,
+            None => Vec::new(),
+        };
+        Ok(SearchDiagnostics {
+            stages,
+            eliminating_constraint,
+            suggestions,
+        })
+    }
+
+    /// The error of a search without matches, naming the constraint that eliminated
+    /// the last remaining candidates
+    pub async fn no_match(&self, pool: &PgPool) -> PartError {
+        match self.count_stages(pool).await {
+            Ok(stages) => PartError::NoMatch {
+                constraint: eliminating_constraint(&stages).map(str::to_string),
+            },
+            Err(error) => PartError::Database(error),
+        }
+    }
+}
+
+/// The first constraint after which no part is left
+pub fn eliminating_constraint(stages: &[StageCount]) -> Option<&str> {
+    stages
+        .iter()
+        .find(|stage| stage.remaining == 0)
+        .map(|stage| stage.constraint.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage(constraint: &str, remaining: i64) -> StageCount {
+        StageCount {
+            constraint: constraint.to_string(),
+            remaining,
+        }
+    }
+
+    #[test]
+    fn test_eliminating_constraint() {
+        let stages = [
+            stage("category", 40),
+            stage("value", 12),
+            stage("package", 3),
+            stage("stock", 0),
+            stage("basic_only", 0),
+        ];
+        assert_eq!(eliminating_constraint(&stages), Some("stock"));
+        assert_eq!(eliminating_constraint(&stages[..3]), None);
+    }
+
+    #[test]
+    fn test_adjacent_packages() {
+        let constraints = SearchConstraints {
+            packages: Some(package_aliases("0603")),
+            ..Default::default()
+        };
+        let adjacent = constraints.adjacent_packages().unwrap();
+        assert!(adjacent.contains(&"0402".to_string()));
+        assert!(adjacent.contains(&"0805".to_string()));
+        assert!(!adjacent.contains(&"0603".to_string()));
+
+        assert_eq!(SearchConstraints::default().adjacent_packages(), None);
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::jlc::quantity::power_of_ten;
+
+/// IEC 60063 preferred number series, the number is the count of values per decade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ESeries {
+    E6,
+    E12,
+    E24,
+    E48,
+    E96,
+    E192,
+}
+
+const E6: [u16; 6] = [100, 150, 220, 330, 470, 680];
+
+const E12: [u16; 12] = [100, 120, 150, 180, 220, 270, 330, 390, 470, 560, 680, 820];
+
+const E24: [u16; 24] = [
+    100, 110, 120, 130, 150, 160, 180, 200, 220, 240, 270, 300, 330, 360, 390, 430, 470, 510, 560,
+    620, 680, 750, 820, 910,
+];
+
+/// E48 and E96 are every fourth and every second value of E192
+const E192: [u16; 192] = [
+    100, 101, 102, 104, 105, 106, 107, 109, 110, 111, 113, 114, 115, 117, 118, 120, 121, 123, 124,
+    126, 127, 129, 130, 132, 133, 135, 137, 138, 140, 142, 143, 145, 147, 149, 150, 152, 154, 156,
+    158, 160, 162, 164, 165, 167, 169, 172, 174, 176, 178, 180, 182, 184, 187, 189, 191, 193, 196,
+    198, 200, 203, 205, 208, 210, 213, 215, 218, 221, 223, 226, 229, 232, 234, 237, 240, 243, 246,
+    249, 252, 255, 258, 261, 264, 267, 271, 274, 277, 280, 284, 287, 291, 294, 298, 301, 305, 309,
+    312, 316, 320, 324, 328, 332, 336, 340, 344, 348, 352, 357, 361, 365, 370, 374, 379, 383, 388,
+    392, 397, 402, 407, 412, 417, 422, 427, 432, 437, 442, 448, 453, 459, 464, 470, 475, 481, 487,
+    493, 499, 505, 511, 517, 523, 530, 536, 542, 549, 556, 562, 569, 576, 583, 590, 597, 604, 612,
+    619, 626, 634, 642, 649, 657, 665, 673, 681, 690, 698, 706, 715, 723, 732, 741, 750, 759, 768,
+    777, 787, 796, 806, 816, 825, 835, 845, 856, 866, 876, 887, 898, 909, 920, 931, 942, 953, 965,
+    976, 988,
+];
+
+impl ESeries {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ESeries::E6 => "E6",
+            ESeries::E12 => "E12",
+            ESeries::E24 => "E24",
+            ESeries::E48 => "E48",
+            ESeries::E96 => "E96",
+            ESeries::E192 => "E192",
+        }
+    }
+
+    /// Three significant digits of the values of a decade, from 100 for 1.00 to below 1000
+    fn mantissas(&self) -> Vec<u16> {
+        match self {
+            ESeries::E6 => E6.to_vec(),
+            ESeries::E12 => E12.to_vec(),
+            ESeries::E24 => E24.to_vec(),
+            ESeries::E48 => E192.iter().step_by(4).copied().collect(),
+            ESeries::E96 => E192.iter().step_by(2).copied().collect(),
+            ESeries::E192 => E192.to_vec(),
+        }
+    }
+
+    /// Values of the series from 10^decade to below 10^(decade + 1)
+    fn decade_values(&self, decade: i32) -> impl Iterator<Item = f64> {
+        self.mantissas()
+            .into_iter()
+            .map(move |mantissa| scaled(mantissa, decade - 2))
+    }
+
+    /// Values of the series around a positive value, from the decade below to the decade above
+    fn neighbourhood(&self, value: f64) -> impl Iterator<Item = f64> + '_ {
+        let decade = value.log10().floor() as i32;
+        (decade - 1..=decade + 1).flat_map(|decade| self.decade_values(decade))
+    }
+
+    /// The value of the series closest to `value` on a logarithmic scale,
+    /// a value that is not positive is returned as is
+    pub fn snap(&self, value: f64) -> f64 {
+        if !(value.is_finite() && value > 0.0) {
+            return value;
+        }
+        self.neighbourhood(value)
+            .min_by(|a, b| {
+                let distance = |candidate: &f64| (candidate / value).ln().abs();
+                distance(a).total_cmp(&distance(b))
+            })
+            .unwrap_or(value)
+    }
+
+    /// The values of the series between `min` and `max`, both inclusive
+    pub fn values_between(&self, min: f64, max: f64) -> Vec<f64> {
+        if !(min.is_finite() && max.is_finite() && min > 0.0 && min <= max) {
+            return Vec::new();
+        }
+        // values parsed from a request may be off by a rounding error
+        let (min, max) = (min * (1.0 - 1e-9), max * (1.0 + 1e-9));
+        let (first, last) = (min.log10().floor() as i32, max.log10().floor() as i32);
+        (first - 1..=last + 1)
+            .flat_map(|decade| self.decade_values(decade))
+            .filter(|value| (min..=max).contains(value))
+            .collect()
+    }
+}
+
+/// The double closest to mantissa * 10^exponent
+fn scaled(mantissa: u16, exponent: i32) -> f64 {
+    if exponent >= 0 {
+        mantissa as f64 * power_of_ten(exponent)
+    } else {
+        mantissa as f64 / power_of_ten(-exponent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_series_sizes() {
+        for (series, size) in [
+            (ESeries::E6, 6),
+            (ESeries::E12, 12),
+            (ESeries::E24, 24),
+            (ESeries::E48, 48),
+            (ESeries::E96, 96),
+            (ESeries::E192, 192),
+        ] {
+            assert_eq!(series.mantissas().len(), size);
+        }
+        assert!(ESeries::E96.mantissas().contains(&487));
+        assert!(ESeries::E48.mantissas().contains(&909));
+    }
+
+    #[test]
+    fn test_snap() {
+        assert_eq!(ESeries::E24.snap(4870.0), 4700.0);
+        assert_eq!(ESeries::E96.snap(4870.0), 4870.0);
+        assert_eq!(ESeries::E192.snap(4880.0), 4870.0);
+        assert_eq!(ESeries::E6.snap(100_000.0), 100_000.0);
+        assert_eq!(ESeries::E12.snap(0.0095), 0.01);
+        assert_eq!(ESeries::E12.snap(9.5), 10.0);
+        assert_eq!(ESeries::E6.snap(0.39), 0.33);
+        assert_eq!(ESeries::E24.snap(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_values_between() {
+        let values = ESeries::E12.values_between(1000.0, 10_000.0);
+        assert_eq!(values.len(), 13);
+        assert_eq!(values.first(), Some(&1000.0));
+        assert_eq!(values.last(), Some(&10_000.0));
+        assert_eq!(ESeries::E6.values_between(0.1, 0.3), vec![0.1, 0.15, 0.22]);
+        assert!(ESeries::E24.values_between(10.0, 1.0).is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt;
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json, Response};
+
+use crate::jlc::v2::models::NoPartFound;
+
+/// Error of a part search, shared by the v1 and v2 services so a client can tell
+/// an invalid request from a failing database from a search without matches
+#[derive(Debug)]
+pub enum PartError {
+    /// The request failed validation
+    InvalidRequest(String),
+    /// The unit of a requested value is not known
+    UnknownUnit(String),
+    /// The requested part type cannot be searched
+    UnsupportedPartType(String),
+    /// No part matches the request, `constraint` is the filter that eliminated
+    /// the last remaining candidates when it is known
+    NoMatch { constraint: Option<String> },
+    /// A requested resource such as a part or category does not exist
+    NotFound(String),
+    /// The database query failed
+    Database(sqlx::Error),
+    /// Any other server side failure
+    Internal(String),
+}
+
+impl PartError {
+    /// HTTP status of the error
+    pub fn status(&self) -> StatusCode {
+        match self {
+            PartError::InvalidRequest(_)
+            | PartError::UnknownUnit(_)
+            | PartError::UnsupportedPartType(_) => StatusCode::BAD_REQUEST,
+            PartError::NoMatch { .. } | PartError::NotFound(_) => StatusCode::NOT_FOUND,
+            PartError::Database(_) | PartError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Stable machine readable code of the error
+    pub fn code(&self) -> &'static str {
+        match self {
+            PartError::InvalidRequest(_) => "invalid_request",
+            PartError::UnknownUnit(_) => "unknown_unit",
+            PartError::UnsupportedPartType(_) => "unsupported_part_type",
+            PartError::NoMatch { .. } => "no_part_found",
+            PartError::NotFound(_) => "not_found",
+            PartError::Database(_) => "database_error",
+            PartError::Internal(_) => "internal_error",
+        }
+    }
+
+    /// Response body of the error
+    pub fn body(&self) -> NoPartFound {
+        let constraint = match self {
+            PartError::NoMatch { constraint } => constraint.clone(),
+            _ => None,
+        };
+        NoPartFound {
+            code: self.status().as_u16() as i64,
+            error: self.code().to_string(),
+            message: self.to_string(),
+            constraint,
+        }
+    }
+
+    /// Status and JSON body of the error, as returned by the endpoints
+    pub fn response(&self) -> (StatusCode, Response) {
+        (self.status(), Json(self.body()).into_response())
+    }
+}
+
+impl fmt::Display for PartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PartError::InvalidRequest(message) | PartError::NotFound(message) => {
+                write!(f, "{}", message)
+            }
+            PartError::UnknownUnit(unit) => write!(f, "Unknown unit: {}", unit),
+            PartError::UnsupportedPartType(part_type) => {
+                write!(f, "Unsupported part type: {}", part_type)
+            }
+            PartError::NoMatch {
+                constraint: Some(constraint),
+            } => write!(
+                f,
+                "No part found, no candidates are left after the {} constraint",
+                constraint
+            ),
+            PartError::NoMatch { constraint: None } => write!(f, "No part found"),
+            PartError::Database(error) => write!(f, "Internal Server Error: {}", error),
+            PartError::Internal(message) => write!(f, "Internal Server Error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for PartError {}
+
+impl From<sqlx::Error> for PartError {
+    fn from(error: sqlx::Error) -> Self {
+        PartError::Database(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_body() {
+        let body = PartError::UnknownUnit("furlong".to_string()).body();
+        assert_eq!(body.code, 400);
+        assert_eq!(body.error, "unknown_unit");
+
+        let body = PartError::NoMatch {
+            constraint: Some("stock".to_string()),
+        }
+        .body();
+        assert_eq!(body.code, 404);
+        assert_eq!(body.error, "no_part_found");
+        assert_eq!(body.constraint.as_deref(), Some("stock"));
+
+        let body = PartError::from(sqlx::Error::PoolTimedOut).body();
+        assert_eq!(body.code, 500);
+        assert_eq!(body.error, "database_error");
+    }
+}
@@ -1,3 +1,12 @@
+pub mod constraints;
+pub mod e_series;
+pub mod error;
+pub mod mpn;
+pub mod package;
+pub mod price;
+pub mod quantity;
+pub mod ranking;
+pub mod stock;
 pub mod v1;
 pub mod v2;
 
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// How a requested manufacturer part number is matched against the `mfr` column,
+/// every mode ignores case and whitespace
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MpnMatch {
+    /// The whole part number must match
+    #[default]
+    Exact,
+    /// The stored part number must start with the requested one
+    Prefix,
+    /// The stored part number may differ by a few edits, see `max_edit_distance`
+    Fuzzy,
+}
+
+/// Whether a v1 `mpn` is one of the `generic_*` placeholders sent by atopile
+/// instead of a real manufacturer part number
+pub fn is_generic_mpn(mpn: &str) -> bool {
+    let mpn = mpn.trim();
+    mpn.is_empty() || mpn.to_ascii_lowercase().starts_with("generic_")
+}
+
+/// Uppercase a part number and drop its whitespace, matching
+/// the generated `mpn_normalized` column of the parts table
+pub fn normalize_mpn(mpn: &str) -> String {
+    mpn.chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Escape the LIKE wildcards of a part number so it can be used as a literal prefix
+pub fn escape_like(mpn: &str) -> String {
+    mpn.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Number of edits a fuzzy match tolerates, one per four characters and at least one
+pub fn max_edit_distance(mpn: &str) -> usize {
+    (mpn.chars().count() / 4).max(1)
+}
+
+/// Regular expression matching the part numbers that contain one of `max_distance + 1`
+/// consecutive pieces of a normalized part number, every part number within
+/// `max_distance` edits keeps at least one of the pieces intact
+pub fn fuzzy_pattern(mpn: &str, max_distance: usize) -> String {
+    let chars: Vec<char> = mpn.chars().collect();
+    let count = (max_distance + 1).min(chars.len()).max(1);
+    (0..count)
+        .map(|index| {
+            chars[index * chars.len() / count..(index + 1) * chars.len() / count]
+                .iter()
+                .map(|c| {
+                    if c.is_alphanumeric() {
+                        c.to_string()
+                    } else {
+                        format!("\\{c}")
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_generic_mpn() {
+        assert!(is_generic_mpn("generic_resistor"));
+        assert!(is_generic_mpn(""));
+        assert!(!is_generic_mpn("RC0402FR-0710KL"));
+    }
+
+    #[test]
+    fn test_normalize_mpn() {
+        assert_eq!(normalize_mpn(" rc0402 fr-0710kl"), "RC0402FR-0710KL");
+        assert_eq!(escape_like("A_B%"), "A\\_B\\%");
+    }
+
+    #[test]
+    fn test_fuzzy_pattern() {
+        assert_eq!(fuzzy_pattern("0603WAF1002T5E", 3), "060|3WAF|100|2T5E");
+        assert_eq!(fuzzy_pattern("RC-1", 1), "RC|\\-1");
+        assert_eq!(fuzzy_pattern("AB", 3), "A|B");
+    }
+}
@@ -0,0 +1,212 @@
+/// A chip package size with its imperial and metric size codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChipSize {
+    pub imperial: &'static str,
+    pub metric: &'static str,
+}
+
+/// Chip package sizes, ordered from smallest to largest
+pub const CHIP_SIZES: [ChipSize; 13] = [
+    ChipSize {
+        imperial: "008004",
+        metric: "0201",
+    },
+    ChipSize {
+        imperial: "01005",
+        metric: "0402",
+    },
+    ChipSize {
+        imperial: "0201",
+        metric: "0603",
+    },
+    ChipSize {
+        imperial: "0402",
+        metric: "1005",
+    },
+    ChipSize {
+        imperial: "0603",
+        metric: "1608",
+    },
+    ChipSize {
+        imperial: "0805",
+        metric: "2012",
+    },
+    ChipSize {
+        imperial: "1206",
+        metric: "3216",
+    },
+    ChipSize {
+        imperial: "1210",
+        metric: "3225",
+    },
+    ChipSize {
+        imperial: "1812",
+        metric: "4532",
+    },
+    ChipSize {
+        imperial: "2010",
+        metric: "5025",
+    },
+    ChipSize {
+        imperial: "2220",
+        metric: "5750",
+    },
+    ChipSize {
+        imperial: "2512",
+        metric: "6332",
+    },
+    ChipSize {
+        imperial: "2920",
+        metric: "7451",
+    },
+];
+
+/// Look up the chip size of a package name, accepting imperial codes ("0402"),
+/// designator prefixed codes ("R0402", "C0402"), explicitly metric codes
+/// ("1005M", "1005Metric", "0402_1005Metric") and unambiguous bare metric codes ("1005").
+/// Bare codes that are both a valid imperial and metric size are read as imperial.
+pub fn chip_size(package: &str) -> Option<ChipSize> {
+    let package = package.trim().to_ascii_uppercase().replace(' ', "");
+
+    // "0402_1005METRIC" and "0402(1005METRIC)" carry both codes, the first one is imperial
+    let package = package
+        .split(['_', '('])
+        .next()
+        .unwrap_or_default()
+        .to_string();
+
+    // strip a designator prefix such as R, C, L or FB
+    let code = package.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+
+    // an explicit metric suffix
+    let (code, metric) = match code.strip_suffix("METRIC").or(code.strip_suffix('M')) {
+        Some(code) => (code, true),
+        None => (code, false),
+    };
+    if code.is_empty() || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    if metric {
+        return CHIP_SIZES.iter().find(|size| size.metric == code).copied();
+    }
+    CHIP_SIZES
+        .iter()
+        .find(|size| size.imperial == code)
+        .or_else(|| CHIP_SIZES.iter().find(|size| size.metric == code))
+        .copied()
+}
+
+/// Canonical package name, the imperial size code for chip packages
+/// and the trimmed package name otherwise
+pub fn canonical_package(package: &str) -> String {
+    match chip_size(package) {
+        Some(size) => size.imperial.to_string(),
+        None => package.trim().to_string(),
+    }
+}
+
+/// Spellings under which a package can be stored in the parts table,
+/// for use in `package = ANY($n)`
+pub fn package_aliases(package: &str) -> Vec<String> {
+    match chip_size(package) {
+        Some(size) => vec![
+            size.imperial.to_string(),
+            format!("R{}", size.imperial),
+            format!("C{}", size.imperial),
+            format!("L{}", size.imperial),
+            format!("{}_{}Metric", size.imperial, size.metric),
+            format!("{}({} Metric)", size.imperial, size.metric),
+        ],
+        None => vec![package.trim().to_string()],
+    }
+}
+
+/// Aliases of an optional requested package, `None` accepts any package
+pub fn optional_package_aliases(package: &Option<String>) -> Option<Vec<String>> {
+    package.as_deref().map(package_aliases)
+}
+
+/// Aliases of every package a request allows, `None` accepts any package.
+/// The requested packages are combined, and when a maximum chip size is given only the
+/// requested chip packages up to that size, or every chip package up to that size when
+/// no package is requested, are allowed.
+pub fn allowed_package_aliases(
+    package: &Option<String>,
+    packages: &Option<Vec<String>>,
+    max_package_size: &Option<String>,
+) -> Option<Vec<String>> {
+    let requested: Vec<&String> = package.iter().chain(packages.iter().flatten()).collect();
+    let max_index = max_package_size.as_deref().and_then(chip_size_index);
+
+    match max_index {
+        None if requested.is_empty() => None,
+        None => Some(
+            requested
+                .into_iter()
+                .flat_map(|package| package_aliases(package))
+                .collect(),
+        ),
+        Some(max_index) if requested.is_empty() => Some(
+            CHIP_SIZES[..=max_index]
+                .iter()
+                .flat_map(|size| package_aliases(size.imperial))
+                .collect(),
+        ),
+        Some(max_index) => Some(
+            requested
+                .into_iter()
+                .filter(|package| chip_size_index(package).is_some_and(|index| index <= max_index))
+                .flat_map(|package| package_aliases(package))
+                .collect(),
+        ),
+    }
+}
+
+/// Position of a chip package in `CHIP_SIZES`, larger is bigger
+pub fn chip_size_index(package: &str) -> Option<usize> {
+    let size = chip_size(package)?;
+    CHIP_SIZES.iter().position(|chip_size| *chip_size == size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_package() {
+        assert_eq!(canonical_package("0402"), "0402");
+        assert_eq!(canonical_package("R0402"), "0402");
+        assert_eq!(canonical_package("C0402"), "0402");
+        assert_eq!(canonical_package("0402_1005Metric"), "0402");
+        assert_eq!(canonical_package("1005Metric"), "0402");
+        assert_eq!(canonical_package("1608"), "0603");
+        assert_eq!(canonical_package("0603"), "0603");
+        assert_eq!(canonical_package("0603M"), "0201");
+        assert_eq!(canonical_package(" SOT-23 "), "SOT-23");
+        assert_eq!(canonical_package("SMD,4x4mm"), "SMD,4x4mm");
+    }
+
+    #[test]
+    fn test_package_aliases() {
+        let aliases = package_aliases("C0603");
+        assert!(aliases.contains(&"0603".to_string()));
+        assert!(aliases.contains(&"0603_1608Metric".to_string()));
+        assert_eq!(package_aliases("SOT-23"), vec!["SOT-23".to_string()]);
+    }
+
+    #[test]
+    fn test_allowed_package_aliases() {
+        assert_eq!(allowed_package_aliases(&None, &None, &None), None);
+
+        let packages = Some(vec!["0402".to_string(), "0805".to_string()]);
+        let aliases = allowed_package_aliases(&None, &packages, &Some("0603".to_string())).unwrap();
+        assert!(aliases.contains(&"0402".to_string()));
+        assert!(!aliases.contains(&"0805".to_string()));
+
+        let aliases = allowed_package_aliases(&None, &None, &Some("0402".to_string())).unwrap();
+        assert!(aliases.contains(&"0201".to_string()));
+        assert!(aliases.contains(&"R0402".to_string()));
+        assert!(!aliases.contains(&"0603".to_string()));
+    }
+}
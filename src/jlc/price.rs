@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A price break of a part, stored in `parts.price_tiers` as a JSON array ordered by quantity,
+/// the `price_tier` and `unit_price` functions of the database select the tier of an order quantity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PriceTier {
+    /// Smallest order quantity of the tier
+    pub min_quantity: i64,
+    /// Largest order quantity of the tier, `None` for the last tier
+    pub max_quantity: Option<i64>,
+    /// Unit price in USD
+    pub price: f64,
+}
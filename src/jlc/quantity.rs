@@ -0,0 +1,592 @@
+/// Physical unit of a part value, values are handled in the SI unit and only converted
+/// to the unit of the parts table for queries and back for responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Ohm,
+    Farad,
+    Henry,
+}
+
+impl Unit {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Unit::Ohm => "ohm",
+            Unit::Farad => "farad",
+            Unit::Henry => "henry",
+        }
+    }
+
+    /// Symbol of the unit, as reported in v1 responses
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Ohm => "Ω",
+            Unit::Farad => "F",
+            Unit::Henry => "H",
+        }
+    }
+
+    /// Unit of the value of a v1 part type, `None` for an unknown type
+    pub fn of_part_type(part_type: &str) -> Option<Unit> {
+        match part_type {
+            "resistor" => Some(Unit::Ohm),
+            "capacitor" => Some(Unit::Farad),
+            "inductor" => Some(Unit::Henry),
+            _ => None,
+        }
+    }
+
+    /// Power of ten from the SI unit to the unit of the parts table,
+    /// resistances are stored in ohm, capacitances in pF and inductances in pH
+    fn stored_exponent(&self) -> i32 {
+        match self {
+            Unit::Ohm => 0,
+            Unit::Farad | Unit::Henry => 12,
+        }
+    }
+
+    /// Convert a value in the SI unit to the unit of the parts table,
+    /// rounded to 12 significant digits so 100 nF is stored as exactly 100000 pF
+    pub fn stored(&self, value: f64) -> f64 {
+        round_significant(value * power_of_ten(self.stored_exponent()), 12)
+    }
+
+    /// Convert a value in the unit of the parts table to the SI unit
+    pub fn from_stored(&self, value: f64) -> f64 {
+        round_significant(value / power_of_ten(self.stored_exponent()), 12)
+    }
+}
+
+/// The double closest to 10^exponent for exponents up to 22, beyond that powers of ten are not exact
+pub(crate) fn power_of_ten(exponent: i32) -> f64 {
+    if exponent >= 0 {
+        10f64.powi(exponent)
+    } else {
+        1.0 / 10f64.powi(-exponent)
+    }
+}
+
+/// The double closest to the value rounded to the given number of significant digits,
+/// formatting rounds exactly where scaling by a power of ten above 10^22 would not
+fn round_significant(value: f64, digits: usize) -> f64 {
+    if value == 0.0 || !value.is_finite() {
+        return value;
+    }
+    format!("{:.*e}", digits - 1, value)
+        .parse()
+        .unwrap_or(value)
+}
+
+/// Tolerance of a value, an absolute tolerance is in the SI unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    Percent(f64),
+    Absolute(f64),
+}
+
+/// A value in its SI unit, with the unit and tolerance when they are known
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: Option<Unit>,
+    pub tolerance: Option<Tolerance>,
+}
+
+impl Quantity {
+    /// Smallest and largest value allowed by the tolerance, the value itself without tolerance
+    pub fn bounds(&self) -> (f64, f64) {
+        let deviation = match self.tolerance {
+            Some(Tolerance::Percent(percent)) => self.value.abs() * percent / 100.0,
+            Some(Tolerance::Absolute(deviation)) => deviation,
+            None => 0.0,
+        };
+        (self.value - deviation, self.value + deviation)
+    }
+}
+
+/// SI prefixes with their power of ten, the words and SPICE's "meg" are case insensitive
+const PREFIXES: [(&str, i32); 19] = [
+    ("pico", -12),
+    ("nano", -9),
+    ("micro", -6),
+    ("milli", -3),
+    ("kilo", 3),
+    ("mega", 6),
+    ("giga", 9),
+    ("meg", 6),
+    ("p", -12),
+    ("n", -9),
+    ("u", -6),
+    ("µ", -6),
+    ("μ", -6),
+    ("m", -3),
+    ("k", 3),
+    ("K", 3),
+    ("M", 6),
+    ("G", 9),
+    ("", 0),
+];
+
+/// Unit symbols and names, the names are case insensitive
+const UNITS: [(&str, Unit); 15] = [
+    ("ohms", Unit::Ohm),
+    ("ohm", Unit::Ohm),
+    ("Ω", Unit::Ohm),
+    ("\u{2126}", Unit::Ohm),
+    ("R", Unit::Ohm),
+    ("r", Unit::Ohm),
+    ("farads", Unit::Farad),
+    ("farad", Unit::Farad),
+    ("F", Unit::Farad),
+    ("f", Unit::Farad),
+    ("henries", Unit::Henry),
+    ("henrys", Unit::Henry),
+    ("henry", Unit::Henry),
+    ("H", Unit::Henry),
+    ("h", Unit::Henry),
+];
+
+/// Whether a word is a case insensitive name rather than a case sensitive symbol
+fn is_name(word: &str) -> bool {
+    word.chars().count() > 1
+}
+
+/// Multiplier of an SI prefix such as "k", "µ" or "micro", `None` for an unknown prefix
+pub fn prefix_multiplier(prefix: &str) -> Option<f64> {
+    prefix_exponent(prefix).map(power_of_ten)
+}
+
+/// Power of ten of an SI prefix, `None` for an unknown prefix
+fn prefix_exponent(prefix: &str) -> Option<i32> {
+    PREFIXES
+        .iter()
+        .find(|(name, _)| {
+            if is_name(name) {
+                name.eq_ignore_ascii_case(prefix)
+            } else {
+                *name == prefix
+            }
+        })
+        .map(|(_, exponent)| *exponent)
+}
+
+/// Split a unit such as "kΩ", "uF" or "kiloohm" into the power of ten of its prefix
+/// and its unit, a bare prefix has no unit
+fn parse_unit(unit: &str) -> Option<(i32, Option<Unit>)> {
+    let unit: String = unit.chars().filter(|c| !c.is_whitespace()).collect();
+    let suffix = UNITS.iter().find(|(name, _)| {
+        unit.len() >= name.len()
+            && unit.is_char_boundary(unit.len() - name.len())
+            && if is_name(name) {
+                unit[unit.len() - name.len()..].eq_ignore_ascii_case(name)
+            } else {
+                unit.ends_with(name)
+            }
+    });
+    match suffix {
+        Some((name, parsed)) => {
+            prefix_exponent(&unit[..unit.len() - name.len()]).map(|e| (e, Some(*parsed)))
+        }
+        None => prefix_exponent(&unit).map(|exponent| (exponent, None)),
+    }
+}
+
+/// Multiplier from a value in the given unit, e.g. "kΩ", "microfarad" or "nH", to the SI unit.
+/// `None` for an unknown unit or a unit of another kind, a bare prefix is read as the expected unit.
+pub fn si_multiplier(unit: &str, expected: Unit) -> Option<f64> {
+    match parse_unit(unit)? {
+        (exponent, None) => Some(power_of_ten(exponent)),
+        (exponent, Some(parsed)) if parsed == expected => Some(power_of_ten(exponent)),
+        _ => None,
+    }
+}
+
+/// Parse a value with an optional prefix and unit in engineering notation ("100nF", "2.2 µH",
+/// "1e-6F") or as an RKM code ("4k7", "R47", "2n2"), without tolerance
+fn parse_value(input: &str) -> Result<(f64, Option<Unit>), String> {
+    let value: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let invalid = || format!("Invalid value \"{}\"", input.trim());
+    // a number that overflows a double, e.g. "1e400", is not a usable value
+    let finite = |value: f64, unit: Option<Unit>| {
+        if value.is_finite() {
+            Ok((value, unit))
+        } else {
+            Err(invalid())
+        }
+    };
+
+    // an RKM code replaces the decimal point by the prefix or R for ohm
+    let split = value
+        .char_indices()
+        .find(|(_, c)| !c.is_ascii_digit())
+        .map(|(index, c)| (index, c, index + c.len_utf8()));
+    if let Some((index, marker, rest)) = split {
+        let fraction_len = value[rest..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len() - rest);
+        let marker_multiplier = match marker {
+            'R' | 'r' => Some((1.0, Some(Unit::Ohm))),
+            _ => prefix_multiplier(&marker.to_string()).map(|multiplier| (multiplier, None)),
+        };
+        if let (true, Some((multiplier, marker_unit))) = (fraction_len > 0, marker_multiplier) {
+            let number: f64 = format!("{}.{}", &value[..index], &value[rest..rest + fraction_len])
+                .parse()
+                .map_err(|_| invalid())?;
+            let suffix = &value[rest + fraction_len..];
+            let unit = match (marker_unit, suffix.is_empty()) {
+                (marker_unit, true) => marker_unit,
+                (_, false) => match parse_unit(suffix) {
+                    Some((0, Some(unit))) if marker_unit.is_none_or(|m| m == unit) => Some(unit),
+                    _ => return Err(invalid()),
+                },
+            };
+            return finite(number * multiplier, unit);
+        }
+    }
+
+    // the longest leading number, followed by the prefix and unit
+    let number_end = (1..=value.len())
+        .rev()
+        .filter(|end| value.is_char_boundary(*end))
+        .find(|end| {
+            value[..*end].starts_with(|c: char| c.is_ascii_digit() || c == '.')
+                && value[..*end].parse::<f64>().is_ok()
+        })
+        .ok_or_else(invalid)?;
+    let number: f64 = value[..number_end].parse().map_err(|_| invalid())?;
+    let (exponent, unit) = parse_unit(&value[number_end..]).ok_or_else(invalid)?;
+    finite(number * power_of_ten(exponent), unit)
+}
+
+/// Parse a value with an optional inline tolerance, e.g. "4k7", "100nF", "2.2µH ±10%"
+/// or "10pF +/- 0.5pF". The value is converted to its SI unit.
+pub fn parse_quantity(input: &str) -> Result<Quantity, String> {
+    let (value, tolerance) = match ["±", "+/-", "+-"]
+        .iter()
+        .find_map(|separator| input.split_once(separator))
+    {
+        Some((value, tolerance)) => (value, Some(tolerance.trim())),
+        None => (input, None),
+    };
+    let (value, unit) = parse_value(value)?;
+
+    let tolerance = match tolerance {
+        None => None,
+        Some(tolerance) => match tolerance.strip_suffix('%') {
+            Some(percent) => {
+                Some(Tolerance::Percent(percent.trim().parse().map_err(
+                    |_| format!("Invalid tolerance \"{}\"", tolerance),
+                )?))
+            }
+            None => {
+                let (deviation, deviation_unit) = parse_value(tolerance)?;
+                if deviation_unit.is_some() && unit.is_some() && deviation_unit != unit {
+                    return Err(format!(
+                        "The tolerance \"{}\" has another unit than the value",
+                        tolerance
+                    ));
+                }
+                Some(Tolerance::Absolute(deviation))
+            }
+        },
+    };
+    match tolerance {
+        Some(Tolerance::Percent(percent)) if !(0.0..=100.0).contains(&percent) => {
+            Err("Tolerance percentage must be between 0 and 100".to_string())
+        }
+        Some(Tolerance::Absolute(deviation)) if deviation < 0.0 => {
+            Err("Absolute tolerance must be positive".to_string())
+        }
+        _ => Ok(Quantity {
+            value,
+            unit,
+            tolerance,
+        }),
+    }
+}
+
+/// The value fields of a v2 request, either a value string or a value with its unit,
+/// and the separate tolerance fields
+pub struct ValueFields<'a> {
+    pub value_string: Option<&'a str>,
+    pub value: Option<f64>,
+    /// SI multiplier of the unit of `value`
+    pub multiplier: Option<f64>,
+    pub tolerance_percentage: Option<f64>,
+    pub absolute_tolerance: Option<f64>,
+    /// SI multiplier of the unit of `absolute_tolerance`
+    pub absolute_tolerance_multiplier: Option<f64>,
+}
+
+/// The requested value of the given unit with its tolerance, from the value string or the
+/// value and unit fields. The tolerance is given inline or by exactly one of the tolerance fields.
+pub fn request_quantity(fields: ValueFields, unit: Unit) -> Result<Quantity, String> {
+    let mut quantity = match (fields.value_string, fields.value, fields.multiplier) {
+        (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+            return Err("Either value_string or value and unit must be set, not both".to_string())
+        }
+        (Some(value_string), None, None) => parse_quantity(value_string)?,
+        (None, Some(value), Some(multiplier)) => Quantity {
+            value: value * multiplier,
+            unit: Some(unit),
+            tolerance: None,
+        },
+        (None, _, _) => return Err("Either value_string or value and unit must be set".to_string()),
+    };
+    if quantity.unit.is_some_and(|parsed| parsed != unit) {
+        return Err(format!("value_string must be a value in {}", unit.name()));
+    }
+    quantity.unit = Some(unit);
+
+    let tolerance_fields =
+        fields.tolerance_percentage.is_some() || fields.absolute_tolerance.is_some();
+    if quantity.tolerance.is_some() {
+        if tolerance_fields {
+            return Err(
+                "Either an inline tolerance or the tolerance fields must be set, not both"
+                    .to_string(),
+            );
+        }
+        return Ok(quantity);
+    }
+
+    if fields
+        .tolerance_percentage
+        .is_some_and(|percent| !(0.0..=100.0).contains(&percent))
+    {
+        return Err("Tolerance percentage must be between 0 and 100".to_string());
+    }
+    // validate that either absolute_tolerance and tolerance_percentage is set, not both and not neither
+    quantity.tolerance = match (fields.absolute_tolerance, fields.tolerance_percentage) {
+        (Some(_), Some(_)) => {
+            return Err(
+                "Either absolute_tolerance or tolerance_percentage must be set, not both"
+                    .to_string(),
+            )
+        }
+        (None, None) => {
+            return Err(
+                "Either absolute_tolerance or tolerance_percentage must be set, not neither"
+                    .to_string(),
+            )
+        }
+        (None, Some(percent)) => Some(Tolerance::Percent(percent)),
+        (Some(deviation), None) => match fields.absolute_tolerance_multiplier {
+            Some(multiplier) => Some(Tolerance::Absolute(deviation * multiplier)),
+            None => {
+                return Err(
+                    "If absolute_tolerance is set, absolute_tolerance_unit must also be set"
+                        .to_string(),
+                )
+            }
+        },
+    };
+    Ok(quantity)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::jlc::v1::jlc_models::JLCValue;
+    use crate::jlc::v2::models::capacitor::CapacitorUnit;
+    use crate::jlc::v2::models::inductor::InductorUnit;
+    use crate::jlc::v2::models::resistor::ResistorUnit;
+
+    const ALL_UNITS: [Unit; 3] = [Unit::Ohm, Unit::Farad, Unit::Henry];
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= b.abs() * 1e-12, "{} != {}", a, b);
+    }
+
+    fn value(input: &str) -> (f64, Option<Unit>) {
+        let quantity = parse_quantity(input).unwrap();
+        (quantity.value, quantity.unit)
+    }
+
+    #[test]
+    fn test_engineering_notation() {
+        let (farad, unit) = value("100nF");
+        assert_close(farad, 100e-9);
+        assert_eq!(unit, Some(Unit::Farad));
+        assert_close(value("2.2 µH").0, 2.2e-6);
+        assert_close(value("2.2μH").0, 2.2e-6);
+        assert_close(value("4.7kΩ").0, 4.7e3);
+        assert_close(value("4.7 kOhm").0, 4.7e3);
+        assert_close(value("1e-6F").0, 1e-6);
+        assert_close(value("10 microfarad").0, 10e-6);
+        assert_close(value("1.5MEG").0, 1.5e6);
+        assert_eq!(value("10k"), (10e3, None));
+        assert_eq!(value("10R"), (10.0, Some(Unit::Ohm)));
+    }
+
+    #[test]
+    fn test_rkm_code() {
+        assert_close(value("4k7").0, 4.7e3);
+        assert_eq!(value("R47"), (0.47, Some(Unit::Ohm)));
+        assert_eq!(value("4R7"), (4.7, Some(Unit::Ohm)));
+        assert_close(value("2n2").0, 2.2e-9);
+        assert_eq!(value("2n2F").1, Some(Unit::Farad));
+        assert_close(value("1M5").0, 1.5e6);
+        assert!(parse_quantity("4k7F2").is_err());
+    }
+
+    #[test]
+    fn test_inline_tolerance() {
+        let quantity = parse_quantity("2.2µH ±10%").unwrap();
+        assert_eq!(quantity.tolerance, Some(Tolerance::Percent(10.0)));
+        let (min, max) = quantity.bounds();
+        assert_close(min, 1.98e-6);
+        assert_close(max, 2.42e-6);
+
+        let quantity = parse_quantity("10pF +/- 0.5pF").unwrap();
+        assert_close(quantity.bounds().0, 9.5e-12);
+        assert!(parse_quantity("10pF ± 0.5nH").is_err());
+        assert!(parse_quantity("10k ±150%").is_err());
+        assert!(parse_quantity("ten ohm").is_err());
+        assert!(parse_quantity("").is_err());
+        assert!(parse_quantity("1e400").is_err());
+        assert!(parse_quantity("1e300T").is_err());
+        assert!(parse_quantity("10k ± 1e400").is_err());
+    }
+
+    #[test]
+    fn test_si_multiplier() {
+        assert_eq!(si_multiplier("kΩ", Unit::Ohm), Some(1e3));
+        assert_eq!(si_multiplier("kiloohm", Unit::Ohm), Some(1e3));
+        assert_eq!(si_multiplier("mΩ", Unit::Ohm), Some(1e-3));
+        assert_eq!(si_multiplier("nF", Unit::Farad), Some(1e-9));
+        assert_eq!(si_multiplier("microhenry", Unit::Henry), Some(1e-6));
+        assert_eq!(si_multiplier("uF", Unit::Ohm), None);
+        assert_eq!(Unit::Farad.stored(100.0 * 1e-9), 100_000.0);
+        assert_eq!(Unit::Farad.from_stored(1.188), 1.188e-12);
+        assert_eq!(si_multiplier("furlong", Unit::Ohm), None);
+    }
+
+    #[test]
+    fn test_request_quantity() {
+        let fields = ValueFields {
+            value_string: Some("4k7 ±1%"),
+            value: None,
+            multiplier: None,
+            tolerance_percentage: None,
+            absolute_tolerance: None,
+            absolute_tolerance_multiplier: None,
+        };
+        let quantity = request_quantity(fields, Unit::Ohm).unwrap();
+        assert_close(quantity.value, 4.7e3);
+        assert_eq!(quantity.tolerance, Some(Tolerance::Percent(1.0)));
+
+        let fields = ValueFields {
+            value_string: None,
+            value: Some(100.0),
+            multiplier: Some(1e-9),
+            tolerance_percentage: None,
+            absolute_tolerance: Some(5.0),
+            absolute_tolerance_multiplier: Some(1e-9),
+        };
+        let quantity = request_quantity(fields, Unit::Farad).unwrap();
+        assert_close(quantity.bounds().1, 105e-9);
+
+        let fields = ValueFields {
+            value_string: Some("100nF"),
+            value: None,
+            multiplier: None,
+            tolerance_percentage: Some(10.0),
+            absolute_tolerance: None,
+            absolute_tolerance_multiplier: None,
+        };
+        assert!(request_quantity(fields, Unit::Henry).is_err());
+    }
+
+    fn assert_near(a: f64, b: f64) {
+        assert!((a - b).abs() <= b.abs() * 1e-11, "{} != {}", a, b);
+    }
+
+    /// Prefix and unit multipliers of the v2 request units, with the unit symbol
+    fn request_units() -> Vec<(&'static str, f64, Unit)> {
+        let resistor = [
+            ResistorUnit::MegaOhm,
+            ResistorUnit::KiloOhm,
+            ResistorUnit::Ohm,
+            ResistorUnit::MilliOhm,
+            ResistorUnit::MicroOhm,
+            ResistorUnit::NanoOhm,
+            ResistorUnit::PicoOhm,
+        ]
+        .map(|unit| (unit.prefix(), unit.multiplier(), Unit::Ohm));
+        let capacitor = [
+            CapacitorUnit::PicoFarad,
+            CapacitorUnit::NanoFarad,
+            CapacitorUnit::MicroFarad,
+            CapacitorUnit::MilliFarad,
+            CapacitorUnit::Farad,
+            CapacitorUnit::KiloFarad,
+            CapacitorUnit::MegaFarad,
+        ]
+        .map(|unit| (unit.prefix(), unit.multiplier(), Unit::Farad));
+        let inductor = [
+            InductorUnit::PicoHenry,
+            InductorUnit::NanoHenry,
+            InductorUnit::MicroHenry,
+            InductorUnit::MilliHenry,
+            InductorUnit::Henry,
+            InductorUnit::KiloHenry,
+            InductorUnit::MegaHenry,
+        ]
+        .map(|unit| (unit.prefix(), unit.multiplier(), Unit::Henry));
+        resistor
+            .into_iter()
+            .chain(capacitor)
+            .chain(inductor)
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn test_stored_round_trip(mantissa in 1u32..1_000_000, exponent in -15i32..=9) {
+            for unit in ALL_UNITS {
+                let value: f64 = format!("{}e{}", mantissa, exponent).parse().unwrap();
+                let stored: f64 = format!("{}e{}", mantissa, exponent + unit.stored_exponent())
+                    .parse()
+                    .unwrap();
+                prop_assert_eq!(unit.stored(value), stored);
+                prop_assert_eq!(unit.from_stored(stored), value);
+            }
+        }
+
+        #[test]
+        fn test_unit_string_round_trip(value in 0.001f64..1000.0) {
+            for (prefix, exponent) in PREFIXES {
+                for (name, unit) in UNITS {
+                    let unit_string = format!("{}{}", prefix, name);
+                    let multiplier = si_multiplier(&unit_string, unit).unwrap();
+                    let quantity = parse_quantity(&format!("{}{}", value, unit_string)).unwrap();
+                    prop_assert_eq!(quantity.unit, Some(unit));
+                    assert_near(quantity.value, value * power_of_ten(exponent));
+                    assert_near(quantity.value, value * multiplier);
+                }
+            }
+        }
+
+        #[test]
+        fn test_request_unit_round_trip(value in 0.001f64..1000.0) {
+            for (prefix, multiplier, unit) in request_units() {
+                let quantity = parse_quantity(&format!("{}{}{}", value, prefix, unit.symbol())).unwrap();
+                assert_near(quantity.value, value * multiplier);
+                let si = si_multiplier(&format!("{}{}", prefix, unit.name()), unit).unwrap();
+                assert_near(value * si, value * multiplier);
+            }
+        }
+
+        #[test]
+        fn test_response_value_round_trip(stored in 1e-3f64..1e9) {
+            for unit in ALL_UNITS {
+                let jlc_value = JLCValue::from_stored(unit, stored, stored, stored);
+                prop_assert_eq!(jlc_value.unit.as_str(), unit.symbol());
+                assert_near(jlc_value.nominal, stored / power_of_ten(unit.stored_exponent()));
+                assert_near(jlc_value.stored_nominal(unit).unwrap(), stored);
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Named strategy used to pick the best component out of the matching candidates
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingStrategy {
+    /// Basic and preferred parts first, then closest to the nominal value, then highest stock
+    #[default]
+    PreferBasic,
+    /// Lowest unit price first
+    Cheapest,
+    /// Smallest relative error to the requested nominal value first
+    ClosestToNominal,
+    /// Highest stock first
+    HighestStock,
+    /// Highest weighted score first, see `RankingWeights`
+    Weighted,
+}
+
+/// Weights of the individual criteria for the `weighted` ranking strategy,
+/// every criterion is scored between 0 and 1 relative to all matching parts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct RankingWeights {
+    pub basic: f64,
+    pub price: f64,
+    pub nominal: f64,
+    pub stock: f64,
+    pub joints: f64,
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        RankingWeights {
+            basic: 1.0,
+            price: 1.0,
+            nominal: 1.0,
+            stock: 1.0,
+            joints: 0.0,
+        }
+    }
+}
+
+/// Ranking fields of the part requests
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct RankingOptions {
+    /// Strategy used to pick the best part, defaults to prefer_basic
+    #[serde(rename = "ranking")]
+    pub strategy: Option<RankingStrategy>,
+    /// Criteria weights for the weighted ranking strategy
+    #[serde(rename = "ranking_weights")]
+    pub weights: Option<RankingWeights>,
+}
+
+/// The attributes of a fetched part that are looked at outside of the ranking query
+pub trait Rankable {
+    fn lcsc(&self) -> &str;
+    fn basic(&self) -> bool;
+    /// The electrical value matched by the search, in the same unit as the nominal value
+    fn value(&self) -> Option<f64>;
+}
+
+/// Relative error of a value to the nominal value, `None` if either is unknown
+pub fn relative_error(value: Option<f64>, nominal: Option<f64>) -> Option<f64> {
+    match (value, nominal) {
+        (Some(value), Some(nominal)) if nominal != 0.0 => Some(((value - nominal) / nominal).abs()),
+        (Some(value), Some(_)) => Some(value.abs()),
+        _ => None,
+    }
+}
+
+/// A requested ranking, the candidate query orders the parts by it in SQL
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ranking {
+    pub strategy: RankingStrategy,
+    pub weights: RankingWeights,
+    /// Requested nominal value, used by `closest_to_nominal` and `weighted`
+    pub nominal: Option<f64>,
+    /// LCSC part numbers of extended parts that are ranked as basic parts
+    pub preferred_parts: Vec<String>,
+}
+
+impl Ranking {
+    pub fn new(
+        strategy: Option<RankingStrategy>,
+        weights: Option<RankingWeights>,
+        nominal: Option<f64>,
+    ) -> Self {
+        Ranking {
+            strategy: strategy.unwrap_or_default(),
+            weights: weights.unwrap_or_default(),
+            nominal,
+            preferred_parts: Vec::new(),
+        }
+    }
+
+    /// Rank the given extended parts as basic parts
+    pub fn with_preferred_parts(mut self, preferred_parts: Vec<String>) -> Self {
+        self.preferred_parts = preferred_parts;
+        self
+    }
+
+    /// Whether a part is basic or one of the preferred extended parts
+    pub fn is_basic<T: Rankable>(&self, component: &T) -> bool {
+        component.basic()
+            || self
+                .preferred_parts
+                .iter()
+                .any(|preferred| preferred == component.lcsc())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Part {
+        lcsc: &'static str,
+        basic: bool,
+    }
+
+    impl Rankable for Part {
+        fn lcsc(&self) -> &str {
+            self.lcsc
+        }
+        fn basic(&self) -> bool {
+            self.basic
+        }
+        fn value(&self) -> Option<f64> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_relative_error() {
+        assert_eq!(relative_error(Some(9_500.0), Some(10_000.0)), Some(0.05));
+        assert_eq!(relative_error(Some(0.5), Some(0.0)), Some(0.5));
+        assert_eq!(relative_error(None, Some(10_000.0)), None);
+    }
+
+    #[test]
+    fn test_preferred_parts() {
+        let ranking = Ranking::new(None, None, None).with_preferred_parts(vec!["C1".to_string()]);
+        assert!(ranking.is_basic(&Part {
+            lcsc: "C1",
+            basic: false
+        }));
+        assert!(ranking.is_basic(&Part {
+            lcsc: "C2",
+            basic: true
+        }));
+        assert!(!ranking.is_basic(&Part {
+            lcsc: "C3",
+            basic: false
+        }));
+    }
+}
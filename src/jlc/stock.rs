@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::jlc::error::PartError;
+
+/// Stock and order quantity fields of the part requests
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct OrderOptions {
+    /// Minimum stock a part must have
+    pub min_stock: Option<i64>,
+    /// Number of parts per board, parts that cannot cover the quantity of all boards plus the
+    /// stock safety margin are excluded and the price tier follows from the ordered quantity
+    pub quantity: Option<i64>,
+    /// Number of boards, defaults to 1
+    pub boards: Option<i64>,
+}
+
+impl OrderOptions {
+    /// Validate that the stock requirements are not negative and an order has at least one part
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_stock.is_some_and(|min_stock| min_stock < 0) {
+            return Err("min_stock must not be negative".to_string());
+        }
+        if self.quantity.is_some_and(|quantity| quantity < 1)
+            || self.boards.is_some_and(|boards| boards < 1)
+        {
+            return Err("quantity and boards must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Number of parts ordered for all boards, see `order_quantity`
+    pub fn order_quantity(&self) -> Result<Option<i64>, PartError> {
+        order_quantity(self.quantity, self.boards)
+    }
+
+    /// Stock a part needs to be usable, see `required_stock`
+    pub fn required_stock(&self, safety_margin: f64) -> Result<Option<i64>, PartError> {
+        required_stock(self.min_stock, self.quantity, self.boards, safety_margin)
+    }
+}
+
+/// Number of parts ordered for all boards, `None` when no quantity is requested
+/// and an invalid request when the product does not fit into an i64
+pub fn order_quantity(
+    quantity: Option<i64>,
+    boards: Option<i64>,
+) -> Result<Option<i64>, PartError> {
+    quantity
+        .map(|quantity| {
+            quantity
+                .checked_mul(boards.unwrap_or(1))
+                .ok_or_else(too_many_parts)
+        })
+        .transpose()
+}
+
+/// Stock a part needs to be usable, the ordered quantity plus the safety margin
+/// and at least `min_stock`. `None` accepts any stock.
+pub fn required_stock(
+    min_stock: Option<i64>,
+    quantity: Option<i64>,
+    boards: Option<i64>,
+    safety_margin: f64,
+) -> Result<Option<i64>, PartError> {
+    // the margin is rounded up to whole parts, the float to int cast saturates
+    // so an overflow shows up in the checked addition
+    let ordered = order_quantity(quantity, boards)?
+        .map(|ordered| {
+            ordered
+                .checked_add((ordered as f64 * safety_margin).ceil() as i64)
+                .ok_or_else(too_many_parts)
+        })
+        .transpose()?;
+    Ok(match (min_stock, ordered) {
+        (Some(min_stock), Some(ordered)) => Some(min_stock.max(ordered)),
+        (min_stock, ordered) => min_stock.or(ordered),
+    })
+}
+
+fn too_many_parts() -> PartError {
+    PartError::InvalidRequest("quantity times boards is too large".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_stock() {
+        assert_eq!(required_stock(None, None, Some(10), 0.1).unwrap(), None);
+        assert_eq!(required_stock(Some(5), None, None, 0.1).unwrap(), Some(5));
+        assert_eq!(
+            required_stock(None, Some(4), Some(25), 0.1).unwrap(),
+            Some(110)
+        );
+        assert_eq!(
+            required_stock(Some(500), Some(4), Some(25), 0.0).unwrap(),
+            Some(500)
+        );
+    }
+
+    #[test]
+    fn test_order_overflow() {
+        let error = order_quantity(Some(i64::MAX), Some(2)).unwrap_err();
+        assert_eq!(error.code(), "invalid_request");
+        assert!(required_stock(None, Some(i64::MAX / 2), Some(2), 0.1).is_err());
+        assert_eq!(
+            order_quantity(Some(i64::MAX), None).unwrap(),
+            Some(i64::MAX)
+        );
+    }
+}
@@ -16,32 +16,25 @@ use crate::AppState;
 request_body = JLCPartRequest,
 responses(
     (status = 200, description = "JLC Part Found", body = [JLCPartResponse]),
-    (status = 404, description = "JLC Part Not Found", body = [NoPartFound])
+    (status = 400, description = "Bad Request", body = [NoPartFound]),
+    (status = 404, description = "JLC Part Not Found", body = [NoPartFound]),
+    (status = 500, description = "Internal Server Error", body = [NoPartFound])
 )
 )]
 pub async fn part_request(
     Extension(pool): Extension<PgPool>,
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(payload): Json<JLCPartRequest>,
 ) -> (StatusCode, Response) {
     // insert your application logic here
 
-    let part_response = find_part(pool, payload).await;
-
-    if part_response.is_err() {
-        return (
-            StatusCode::NOT_FOUND,
-            Json(NoPartFound {
-                code: 404,
-                message: part_response.unwrap_err(),
-            })
-            .into_response(),
-        );
+    match find_part(pool, state, payload).await {
+        // this will be converted into a JSON response
+        // with a status code of `200 OK`
+        Ok(part_response) => (StatusCode::OK, Json(part_response).into_response()),
+        // unknown units are a 400, database errors a 500 and searches without matches a 404
+        Err(error) => error.response(),
     }
-
-    // this will be converted into a JSON response
-    // with a status code of `201 Created`
-    (StatusCode::OK, Json(part_response.unwrap()).into_response())
 }
 
 #[cfg(test)]
@@ -62,7 +55,7 @@ mod tests {
         let args = Args::parse();
         let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
 
-        let app_state = AppState {};
+        let app_state = AppState::default();
 
         let app = Router::new()
             .route("/jlc/v1", post(part_request))
@@ -90,5 +83,23 @@ mod tests {
         .json(&json!({"designator_prefix": "C", "mpn": "generic_inductor", "type": "inductor", "value": {"unit": "nanohenry", "min_val": 80.0, "max_val": 120.0, "nominal": 100.0}}))
         .await;
         assert_eq!(response.status_code(), StatusCode::OK);
+
+        // an unknown unit is a bad request, not a missing part
+        let response = server.post("/jlc/v1")
+        .json(&json!({"designator_prefix": "R", "mpn": "generic_resistor", "type": "resistor", "value": {"unit": "furlong", "min_val": 0.95, "max_val": 1.05, "nominal": 1}}))
+        .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.json::<NoPartFound>().error, "unknown_unit");
+
+        // quantities are validated like v2 and an overflowing order is rejected
+        let response = server.post("/jlc/v1")
+        .json(&json!({"designator_prefix": "R", "mpn": "generic_resistor", "type": "resistor", "value": {"unit": "kiloohm", "min_val": 0.95, "max_val": 1.05, "nominal": 1}, "quantity": 0}))
+        .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+        let response = server.post("/jlc/v1")
+        .json(&json!({"designator_prefix": "R", "mpn": "generic_resistor", "type": "resistor", "value": {"unit": "kiloohm", "min_val": 0.95, "max_val": 1.05, "nominal": 1}, "quantity": i64::MAX, "boards": 2}))
+        .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.json::<NoPartFound>().error, "invalid_request");
     }
 }
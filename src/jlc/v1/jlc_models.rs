@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
+use crate::jlc::quantity::{si_multiplier, Unit};
+use crate::jlc::ranking::RankingOptions;
+use crate::jlc::stock::OrderOptions;
+use crate::jlc::v2::models::capacitor::Dielectric;
+
 // LEGACY Request Models
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -12,6 +17,12 @@ pub struct JLCPartRequest {
     pub type_field: String,
     pub value: JLCValue,
     pub package: Option<String>,
+    /// Allowed dielectric classes for capacitors, any dielectric is accepted when omitted
+    pub dielectrics: Option<Vec<Dielectric>>,
+    #[serde(flatten)]
+    pub order: OrderOptions,
+    #[serde(flatten)]
+    pub ranking: RankingOptions,
 }
 
 // LEGACY Request Models
@@ -26,6 +37,34 @@ pub struct JLCValue {
     pub nominal: f64,
 }
 
+impl JLCValue {
+    /// Value of a response, converted from the unit of the parts table to the SI unit
+    pub fn from_stored(unit: Unit, min_val: f64, max_val: f64, nominal: f64) -> Self {
+        JLCValue {
+            unit: unit.symbol().to_string(),
+            min_val: unit.from_stored(min_val),
+            max_val: unit.from_stored(max_val),
+            nominal: unit.from_stored(nominal),
+        }
+    }
+
+    /// Nominal, smallest and largest value in the unit of the parts table,
+    /// `None` if the unit is not a unit of `unit`
+    pub fn stored(&self, unit: Unit) -> Option<(f64, f64, f64)> {
+        let multiplier = si_multiplier(&self.unit, unit)?;
+        Some((
+            unit.stored(self.nominal * multiplier),
+            unit.stored(self.min_val * multiplier),
+            unit.stored(self.max_val * multiplier),
+        ))
+    }
+
+    /// Nominal value in the unit of the parts table, `None` if the unit is not a unit of `unit`
+    pub fn stored_nominal(&self, unit: Unit) -> Option<f64> {
+        self.stored(unit).map(|(nominal, _, _)| nominal)
+    }
+}
+
 // LEGACY Response Models
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -60,6 +99,9 @@ pub struct BestComponent {
     pub dielectric: Option<String>,
     #[serde(rename = "basic_part")]
     pub basic_part: bool,
+    /// Relative error of the part value to the requested nominal value, 0.01 is 1%
+    #[serde(rename = "relative_error")]
+    pub relative_error: Option<f64>,
 }
 
 // LEGACY Response Models
@@ -87,9 +129,5 @@ pub struct FootprintData {
     pub kicad: String,
 }
 
-// Response No Part Found
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
-pub struct NoPartFound {
-    pub code: i64,
-    pub message: String,
-}
+// Response No Part Found, shared with v2
+pub use crate::jlc::v2::models::NoPartFound;
@@ -1,120 +1,91 @@
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::jlc::error::PartError;
+use crate::jlc::mpn::is_generic_mpn;
+use crate::jlc::quantity::Unit;
+use crate::jlc::ranking::{relative_error, Rankable};
 use crate::jlc::v1::jlc_models::*;
 use crate::jlc::v1::jlc_searchers::jlc_find_capacitor::find_capacitor;
 use crate::jlc::v1::jlc_searchers::jlc_find_inductor::find_inductor;
+use crate::jlc::v1::jlc_searchers::jlc_find_mpn::find_mpn;
 use crate::jlc::v1::jlc_searchers::jlc_find_resistor::find_resistor;
+use crate::jlc::v2::models::CandidateRange;
+use crate::AppState;
 
-#[derive(Debug, Clone, sqlx::FromRow)]
-pub struct Component {
-    pub id: i64,
-    pub lcsc: String,
-    pub category_id: i64,
-    pub mfr: Option<String>,
-    pub package: Option<String>,
-    pub joints: i64,
-    pub manufacturer: String,
-    pub basic: bool,
-    pub description: Option<String>,
-    pub datasheet: Option<String>,
-    pub stock: i64,
-    pub price: Option<f64>,
-    pub last_update: sqlx::types::time::PrimitiveDateTime,
-    pub resistance: Option<f64>,
-    pub inductance: Option<f64>,
-    pub capacitance: Option<f64>,
-    pub dielectric: Option<String>,
-    pub current: Option<f64>,
-    pub voltage: Option<f64>,
-}
+// Candidate parts, shared with v2 so both versions rank the same query
+pub use crate::jlc::v2::models::Component;
 
-impl Ord for Component {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        if self.basic && !other.basic {
-            std::cmp::Ordering::Less
-        } else if !self.basic && other.basic {
-            std::cmp::Ordering::Greater
-        } else {
-            other.stock.cmp(&self.stock)
-        }
-    }
+pub async fn find_part(
+    pool: PgPool,
+    state: AppState,
+    request: JLCPartRequest,
+) -> Result<JLCPartResponse, PartError> {
+    let (component_vec, jlc_value) =
+        find_candidates(pool, state, request.clone(), CandidateRange::Best(1)).await?;
+    Ok(component_vec_to_jlcpb_part_response(
+        request,
+        component_vec,
+        jlc_value,
+    ))
 }
 
-impl PartialOrd for Component {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+/// Find the requested range of the ranked parts matching a request together with the converted
+/// requested value, an error when no part matches
+pub async fn find_candidates(
+    pool: PgPool,
+    state: AppState,
+    request: JLCPartRequest,
+    range: CandidateRange,
+) -> Result<(Vec<Component>, JLCValue), PartError> {
+    validate_request(&request).map_err(PartError::InvalidRequest)?;
+    search_candidates(pool, state, request, range).await
 }
 
-impl Eq for Component {}
-
-impl PartialEq for Component {
-    fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
-    }
+/// Validate the stock and quantity fields of a request, with the same rules as v2
+pub(crate) fn validate_request(request: &JLCPartRequest) -> Result<(), String> {
+    request.order.validate()
 }
 
-pub async fn find_part(pool: PgPool, request: JLCPartRequest) -> Result<JLCPartResponse, String> {
+async fn search_candidates(
+    pool: PgPool,
+    state: AppState,
+    request: JLCPartRequest,
+    range: CandidateRange,
+) -> Result<(Vec<Component>, JLCValue), PartError> {
     tracing::info!("Searching JLC part: {:?}", request);
-    if request.type_field == "resistor".to_string() {
-        let option_resistor_vec = find_resistor(pool, request.clone()).await;
-        if option_resistor_vec.is_err() {
-            return Err("No resistor found".to_string());
-        } else {
-            let (component_vec, jlc_value) = option_resistor_vec.unwrap();
-            if component_vec.len() == 0 {
-                return Err("No resistor found".to_string());
-            }
-            return Ok(component_vec_to_jlcpb_part_response(
-                request,
-                component_vec,
-                jlc_value,
-            ));
-        }
-    } else if request.type_field == "capacitor".to_string() {
-        let option_capacitor_vec = find_capacitor(pool, request.clone()).await;
-        if option_capacitor_vec.is_err() {
-            return Err("No capacitor found".to_string());
-        } else {
-            let (component_vec, jlc_value) = option_capacitor_vec.unwrap();
-            if component_vec.len() == 0 {
-                return Err("No capacitor found".to_string());
+    // a specific manufacturer part number wins over the value search when it is stocked
+    if !is_generic_mpn(&request.mpn) {
+        match find_mpn(pool.clone(), state.clone(), request.clone(), range).await {
+            Ok((component_vec, jlc_value)) if !component_vec.is_empty() => {
+                return Ok((component_vec, jlc_value));
             }
-            return Ok(component_vec_to_jlcpb_part_response(
-                request,
-                component_vec,
-                jlc_value,
-            ));
+            Ok(_) => tracing::info!("No part found for mpn {}, searching by value", request.mpn),
+            Err(error) => return Err(error),
         }
-    } else if request.type_field == "inductor".to_string() {
-        let option_inductor_vec = find_inductor(pool, request.clone()).await;
-        if option_inductor_vec.is_err() {
-            return Err("No inductor found".to_string());
-        } else {
-            let (component_vec, jlc_value) = option_inductor_vec.unwrap();
-            if component_vec.len() == 0 {
-                return Err("No inductor found".to_string());
-            }
-            return Ok(component_vec_to_jlcpb_part_response(
-                request,
-                component_vec,
-                jlc_value,
-            ));
-        }
-    } else {
-        Err("Unsupported part type".to_string())
     }
+    // the value searches fail with the eliminating constraint when nothing matches
+    match request.type_field.as_str() {
+        "resistor" => find_resistor(pool, state, request, range).await,
+        "capacitor" => find_capacitor(pool, state, request, range).await,
+        "inductor" => find_inductor(pool, state, request, range).await,
+        _ => Err(PartError::UnsupportedPartType(request.type_field)),
+    }
+}
+
+/// Nominal value of a request in the unit of the parts table, `None` for an unknown type or unit
+fn stored_nominal(request: &JLCPartRequest, jlc_value: &JLCValue) -> Option<f64> {
+    Unit::of_part_type(&request.type_field).and_then(|unit| jlc_value.stored_nominal(unit))
 }
 
+/// Build the response from ranked components, the first one is the best component
 pub fn component_vec_to_jlcpb_part_response(
     request: JLCPartRequest,
-    mut components: Vec<Component>,
+    components: Vec<Component>,
     jlc_value: JLCValue,
 ) -> JLCPartResponse {
-    // return first element of components vector
-    components.sort();
-    let component = components.get(0).unwrap();
+    let nominal = stored_nominal(&request, &jlc_value);
+    let component = components.first().unwrap();
 
     // kicad_footprint is R + package for resistors and C + package for capacitors
     let kicad_footprint = match request.type_field.as_str() {
@@ -127,6 +98,7 @@ pub fn component_vec_to_jlcpb_part_response(
     let best_component = BestComponent {
         dielectric: component.dielectric.clone(),
         basic_part: component.basic,
+        relative_error: relative_error(component.value(), nominal),
         description: component.description.clone().unwrap_or("".to_string()),
         type_field: request.type_field,
         uuid: Uuid::new_v4().to_string(),
@@ -146,15 +118,13 @@ pub fn component_vec_to_jlcpb_part_response(
         mpn: component.mfr.clone().unwrap_or("".to_string()),
         datasheet: component.datasheet.clone().unwrap_or("".to_string()),
         category: "Resistors".to_string(), // not really needed right now
-        lcsc_id: lcsc_id,
+        lcsc_id,
         package: component.package.clone().unwrap_or("".to_string()),
         footprint_data: FootprintData {
             kicad: "standard-library".to_string(),
         },
     };
-    JLCPartResponse {
-        best_component: best_component,
-    }
+    JLCPartResponse { best_component }
 }
 
 #[cfg(test)]
@@ -182,14 +152,70 @@ mod tests {
                 nominal: 4.5,
             },
             package: Some("0603".to_string()),
+            ..Default::default()
         };
-        let resistor_result = find_resistor(pool, request.clone()).await;
+        let resistor_result = find_resistor(
+            pool,
+            AppState::default(),
+            request.clone(),
+            CandidateRange::Best(1),
+        )
+        .await;
         assert!(resistor_result.is_ok());
         let (component_vec, _jlc_value) = resistor_result.unwrap();
-        assert!(component_vec.len() > 0);
+        assert!(!component_vec.is_empty());
         assert!(component_vec[0].package == Some("0603".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_resistor_closest_to_nominal() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+        let request = JLCPartRequest {
+            type_field: "resistor".to_string(),
+            designator_prefix: "R".to_string(),
+            mpn: "generic_resistor".to_string(),
+            value: JLCValue {
+                unit: "kiloohm".to_string(),
+                min_val: 9.5,
+                max_val: 10.5,
+                nominal: 10.0,
+            },
+            package: Some("0402".to_string()),
+            ..Default::default()
+        };
+        let part_response = find_part(pool, AppState::default(), request).await.unwrap();
+        assert_eq!(part_response.best_component.relative_error, Some(0.0));
+        assert_eq!(part_response.best_component.value.nominal, 10_000.0);
+        assert_eq!(part_response.best_component.value.min_val, 9_500.0);
+    }
+
+    #[tokio::test]
+    async fn test_resistor_mpn() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+        let request = JLCPartRequest {
+            type_field: "resistor".to_string(),
+            designator_prefix: "R".to_string(),
+            mpn: "0603WAF1002T5E".to_string(),
+            value: JLCValue {
+                unit: "kiloohm".to_string(),
+                min_val: 9.5,
+                max_val: 10.5,
+                nominal: 10.0,
+            },
+            package: Some("0402".to_string()),
+            ..Default::default()
+        };
+        let part_response = find_part(pool, AppState::default(), request).await.unwrap();
+        assert_eq!(part_response.best_component.lcsc_id, "C25804");
+        assert_eq!(part_response.best_component.relative_error, Some(0.0));
+        assert_eq!(part_response.best_component.value.unit, "Ω");
+        assert_eq!(part_response.best_component.value.nominal, 10_000.0);
+    }
+
     #[tokio::test]
     async fn test_capacitor() {
         dotenv().ok();
@@ -206,11 +232,18 @@ mod tests {
                 nominal: 4.5,
             },
             package: Some("0603".to_string()),
+            ..Default::default()
         };
-        let capacitor_result = find_capacitor(pool, request.clone()).await;
+        let capacitor_result = find_capacitor(
+            pool,
+            AppState::default(),
+            request.clone(),
+            CandidateRange::Best(1),
+        )
+        .await;
         assert!(capacitor_result.is_ok());
         let (component_vec, _jlc_value) = capacitor_result.unwrap();
-        assert!(component_vec.len() > 0);
+        assert!(!component_vec.is_empty());
         assert!(component_vec[0].package == Some("0603".to_string()));
     }
 
@@ -230,11 +263,18 @@ mod tests {
                 nominal: 4.5,
             },
             package: Some("0603".to_string()),
+            ..Default::default()
         };
-        let inductor_result = find_inductor(pool, request.clone()).await;
+        let inductor_result = find_inductor(
+            pool,
+            AppState::default(),
+            request.clone(),
+            CandidateRange::Best(1),
+        )
+        .await;
         assert!(inductor_result.is_ok());
         let (component_vec, _jlc_value) = inductor_result.unwrap();
-        assert!(component_vec.len() > 0);
+        assert!(!component_vec.is_empty());
         assert!(component_vec[0].package == Some("0603".to_string()));
     }
 }
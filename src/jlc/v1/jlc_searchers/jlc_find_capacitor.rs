@@ -1,73 +1,50 @@
 use sqlx::PgPool;
 
+use crate::jlc::constraints::{SearchConstraints, ValueColumn};
+use crate::jlc::error::PartError;
+use crate::jlc::package::optional_package_aliases;
+use crate::jlc::quantity::Unit;
 use crate::jlc::v1::jlc_models::*;
 use crate::jlc::v1::jlc_part_finder::Component;
+use crate::jlc::v2::models::capacitor::dielectric_names;
+use crate::jlc::v2::models::CandidateRange;
+use crate::jlc::v2::services::candidates::constrained_candidates;
+use crate::AppState;
 
 pub async fn find_capacitor(
     pool: PgPool,
+    state: AppState,
     request: JLCPartRequest,
-) -> Result<(Vec<Component>, JLCValue), sqlx::Error> {
-    // get
-    let capacitor_category_id: (i32,) = sqlx::query_as("SELECT id FROM categories WHERE name = 'Capacitors' and subcategory_name = 'Multilayer Ceramic Capacitors MLCC - SMD/SMT'")
-    .fetch_one(&pool).await?;
+    range: CandidateRange,
+) -> Result<(Vec<Component>, JLCValue), PartError> {
+    // value conversion, capacitances are searched in pF
+    let (farad_value, farad_min, farad_max) = request
+        .value
+        .stored(Unit::Farad)
+        .ok_or_else(|| PartError::UnknownUnit(request.value.unit.clone()))?;
 
-    // value conversion
-    let farad_string = request.value.unit.to_string();
-    let (farad_value, farad_max, farad_min) = match farad_string.as_str() {
-        "pF" | "picofarad" => (
-            request.value.nominal,
-            request.value.max_val,
-            request.value.min_val,
-        ),
-        "nF" | "nanofarad" => (
-            request.value.nominal * 1e3,
-            request.value.max_val * 1e3,
-            request.value.min_val * 1e3,
-        ),
-        "μF" | "uF" | "microfarad" => (
-            request.value.nominal * 1e6,
-            request.value.max_val * 1e6,
-            request.value.min_val * 1e6,
-        ),
-        "mF" | "millifarad" => (
-            request.value.nominal * 1e9,
-            request.value.max_val * 1e9,
-            request.value.min_val * 1e9,
-        ),
-        "F" | "farad" => (
-            request.value.nominal * 1e12,
-            request.value.max_val * 1e12,
-            request.value.min_val * 1e12,
-        ),
-        _ => return Err(sqlx::Error::RowNotFound),
-    };
+    let jlc_farad_value = JLCValue::from_stored(Unit::Farad, farad_min, farad_max, farad_value);
 
-    let jlc_farad_value = JLCValue {
-        unit: "F".to_string(),
-        min_val: farad_min,
-        max_val: farad_max,
+    // the same filters as v2, a package matches any of its aliases, a `None` dielectrics
+    // accepts any dielectric and parts that cannot cover the ordered quantity are excluded
+    let constraints = SearchConstraints {
+        category_id: ValueColumn::Capacitance.category_id(&pool).await?,
+        column: ValueColumn::Capacitance,
         nominal: farad_value,
+        min_value: farad_min,
+        max_value: farad_max,
+        packages: optional_package_aliases(&request.package),
+        dielectrics: dielectric_names(&request.dielectrics),
+        min_stock: request.order.required_stock(state.stock_safety_margin)?,
+        preferred_parts: state.preferred_parts(&None),
+        ..Default::default()
     };
 
-    // if request.package is not None, filter components_df on package = request.package
-    if request.package.is_some() {
-        let matching_parts: Vec<Component> = sqlx::query_as!(
-            Component,
-            r#"SELECT id as "id!", lcsc as "lcsc!", category_id as "category_id!", mfr as "mfr?", package as "package?", joints as "joints!", manufacturer as "manufacturer!", basic as "basic!", description as "description?", datasheet as "datasheet?", stock as "stock!", price as "price?", last_update as "last_update!", capacitance as "capacitance?", inductance, resistance, dielectric as "dielectric?", current, voltage FROM parts WHERE category_id = $1 and capacitance between $2 and $3 and package = $4 ORDER BY basic DESC LIMIT 100"#,
-            capacitor_category_id.0,
-            farad_min,
-            farad_max,
-            request.package.unwrap()
-        ).fetch_all(&pool).await?;
-        return Ok((matching_parts, jlc_farad_value));
-    } else {
-        let matching_parts: Vec<Component> = sqlx::query_as!(
-            Component,
-            r#"SELECT id as "id!", lcsc as "lcsc!", category_id as "category_id!", mfr as "mfr?", package as "package?", joints as "joints!", manufacturer as "manufacturer!", basic as "basic!", description as "description?", datasheet as "datasheet?", stock as "stock!", price as "price?", last_update as "last_update!", capacitance as "capacitance?", inductance, resistance, dielectric as "dielectric?", current, voltage FROM parts WHERE category_id = $1 and capacitance between $2 and $3 ORDER BY basic DESC LIMIT 100"#,
-            capacitor_category_id.0,
-            farad_min,
-            farad_max
-        ).fetch_all(&pool).await?;
-        return Ok((matching_parts, jlc_farad_value));
-    }
+    // every matching part is ranked before the best candidates are fetched,
+    // a search without matches names the eliminating constraint
+    let order_quantity = request.order.order_quantity()?;
+    let results =
+        constrained_candidates(&pool, &constraints, &request.ranking, order_quantity, range)
+            .await?;
+    Ok((results.components, jlc_farad_value))
 }
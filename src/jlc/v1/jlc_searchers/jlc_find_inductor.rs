@@ -1,83 +1,48 @@
 use sqlx::PgPool;
 
+use crate::jlc::constraints::{SearchConstraints, ValueColumn};
+use crate::jlc::error::PartError;
+use crate::jlc::package::optional_package_aliases;
+use crate::jlc::quantity::Unit;
 use crate::jlc::v1::jlc_models::*;
 use crate::jlc::v1::jlc_part_finder::Component;
+use crate::jlc::v2::models::CandidateRange;
+use crate::jlc::v2::services::candidates::constrained_candidates;
+use crate::AppState;
 
 pub async fn find_inductor(
     pool: PgPool,
+    state: AppState,
     request: JLCPartRequest,
-) -> Result<(Vec<Component>, JLCValue), sqlx::Error> {
-    // get
-    let inductor_category_id: (i32,) = sqlx::query_as("SELECT id FROM categories WHERE name = 'Inductors/Coils/Transformers' and subcategory_name = 'Inductors (SMD)'")
-    .fetch_one(&pool).await?;
+    range: CandidateRange,
+) -> Result<(Vec<Component>, JLCValue), PartError> {
+    // value conversion, inductances are searched in pH
+    let (henry_value, henry_min, henry_max) = request
+        .value
+        .stored(Unit::Henry)
+        .ok_or_else(|| PartError::UnknownUnit(request.value.unit.clone()))?;
 
-    // value conversion
-    let henry_string = request.value.unit.to_string();
-    let (henry_value, henry_max, henry_min) = match henry_string.as_str() {
-        "pH" | "picohenry" => (
-            request.value.nominal,
-            request.value.max_val,
-            request.value.min_val,
-        ),
-        "nH" | "nanohenry" => (
-            request.value.nominal * 1e3,
-            request.value.max_val * 1e3,
-            request.value.min_val * 1e3,
-        ),
-        "μH" | "uH" | "microhenry" => (
-            request.value.nominal * 1e6,
-            request.value.max_val * 1e6,
-            request.value.min_val * 1e6,
-        ),
-        "mH" | "millihenry" => (
-            request.value.nominal * 1e9,
-            request.value.max_val * 1e9,
-            request.value.min_val * 1e9,
-        ),
-        "kH" | "kilohenry" => (
-            request.value.nominal * 1e15,
-            request.value.max_val * 1e15,
-            request.value.min_val * 1e15,
-        ),
-        "MH" | "megahenry" => (
-            request.value.nominal * 1e18,
-            request.value.max_val * 1e18,
-            request.value.min_val * 1e18,
-        ),
-        "H" | "henry" => (
-            request.value.nominal * 1e12,
-            request.value.max_val * 1e12,
-            request.value.min_val * 1e12,
-        ),
-        _ => return Err(sqlx::Error::RowNotFound),
-    };
+    let jlc_henry_value = JLCValue::from_stored(Unit::Henry, henry_min, henry_max, henry_value);
 
-    let jlc_henry_value = JLCValue {
-        unit: "H".to_string(),
-        min_val: henry_min,
-        max_val: henry_max,
+    // the same filters as v2, a package matches any of its aliases and parts that cannot
+    // cover the ordered quantity are excluded
+    let constraints = SearchConstraints {
+        category_id: ValueColumn::Inductance.category_id(&pool).await?,
+        column: ValueColumn::Inductance,
         nominal: henry_value,
+        min_value: henry_min,
+        max_value: henry_max,
+        packages: optional_package_aliases(&request.package),
+        min_stock: request.order.required_stock(state.stock_safety_margin)?,
+        preferred_parts: state.preferred_parts(&None),
+        ..Default::default()
     };
 
-    // if request.package is not None, filter components_df on package = request.package
-    if request.package.is_some() {
-        let matching_parts: Vec<Component> = sqlx::query_as!(
-            Component,
-            r#"SELECT id as "id!", lcsc as "lcsc!", category_id as "category_id!", mfr as "mfr?", package as "package?", joints as "joints!", manufacturer as "manufacturer!", basic as "basic!", description as "description?", datasheet as "datasheet?", stock as "stock!", price as "price?", last_update as "last_update!", inductance as "inductance?", capacitance, resistance, dielectric as "dielectric?", current, voltage FROM parts WHERE category_id = $1 and inductance between $2 and $3 and package = $4 ORDER BY basic DESC LIMIT 100"#,
-            inductor_category_id.0,
-            henry_min,
-            henry_max,
-            request.package.unwrap()
-        ).fetch_all(&pool).await?;
-        return Ok((matching_parts, jlc_henry_value));
-    } else {
-        let matching_parts: Vec<Component> = sqlx::query_as!(
-            Component,
-            r#"SELECT id as "id!", lcsc as "lcsc!", category_id as "category_id!", mfr as "mfr?", package as "package?", joints as "joints!", manufacturer as "manufacturer!", basic as "basic!", description as "description?", datasheet as "datasheet?", stock as "stock!", price as "price?", last_update as "last_update!", inductance as "inductance?", capacitance, resistance, dielectric as "dielectric?", current, voltage FROM parts WHERE category_id = $1 and inductance between $2 and $3 ORDER BY basic DESC LIMIT 100"#,
-            inductor_category_id.0,
-            henry_min,
-            henry_max
-        ).fetch_all(&pool).await?;
-        return Ok((matching_parts, jlc_henry_value));
-    }
+    // every matching part is ranked before the best candidates are fetched,
+    // a search without matches names the eliminating constraint
+    let order_quantity = request.order.order_quantity()?;
+    let results =
+        constrained_candidates(&pool, &constraints, &request.ranking, order_quantity, range)
+            .await?;
+    Ok((results.components, jlc_henry_value))
 }
@@ -0,0 +1,60 @@
+use sqlx::PgPool;
+
+use crate::jlc::constraints::ValueColumn;
+use crate::jlc::error::PartError;
+use crate::jlc::mpn::normalize_mpn;
+use crate::jlc::quantity::Unit;
+use crate::jlc::ranking::Ranking;
+use crate::jlc::v1::jlc_models::*;
+use crate::jlc::v1::jlc_part_finder::Component;
+use crate::jlc::v2::models::CandidateRange;
+use crate::jlc::v2::services::candidates::CandidateQuery;
+use crate::AppState;
+
+/// Find the parts of the requested type whose manufacturer part number is exactly `request.mpn`,
+/// the returned value is the value of the best part in the SI unit, the requested value without a part
+pub async fn find_mpn(
+    pool: PgPool,
+    state: AppState,
+    request: JLCPartRequest,
+    range: CandidateRange,
+) -> Result<(Vec<Component>, JLCValue), PartError> {
+    // parts that cannot cover the ordered quantity are excluded, a NULL min_stock accepts any stock
+    let min_stock = request.order.required_stock(state.stock_safety_margin)?;
+
+    let unit = Unit::of_part_type(&request.type_field)
+        .ok_or_else(|| PartError::UnsupportedPartType(request.type_field.clone()))?;
+    let (category, subcategory) = ValueColumn::of_unit(unit).category();
+
+    // the parts are ranked like the ones of a value search
+    let mut query = CandidateQuery {
+        order_quantity: request.order.order_quantity()?,
+        ..Default::default()
+    };
+    let part_number = query.bind(normalize_mpn(&request.mpn))?;
+    let category = query.bind(category)?;
+    let subcategory = query.bind(subcategory)?;
+    let min_stock = query.bind(min_stock)?;
+    query.source = format!(
+        "FROM parts p JOIN categories c ON c.id = p.category_id WHERE p.mpn_normalized = {part_number} and c.name = {category} and c.subcategory_name = {subcategory} and ({min_stock}::int8 IS NULL OR p.stock >= {min_stock})"
+    );
+    let ranking = Ranking::new(
+        request.ranking.strategy,
+        request.ranking.weights.clone(),
+        None,
+    )
+    .with_preferred_parts(state.preferred_parts(&None));
+    let matching_parts = query.fetch(&pool, &ranking, range).await?.components;
+
+    // the value of the best part is reported in the SI unit
+    let value = matching_parts.first().and_then(|component| {
+        let stored = match unit {
+            Unit::Ohm => component.resistance,
+            Unit::Farad => component.capacitance,
+            Unit::Henry => component.inductance,
+        }?;
+        Some(JLCValue::from_stored(unit, stored, stored, stored))
+    });
+    let jlc_value = value.unwrap_or(request.value);
+    Ok((matching_parts, jlc_value))
+}
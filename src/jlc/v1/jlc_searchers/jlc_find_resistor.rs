@@ -1,90 +1,48 @@
 use sqlx::PgPool;
 
+use crate::jlc::constraints::{SearchConstraints, ValueColumn};
+use crate::jlc::error::PartError;
+use crate::jlc::package::optional_package_aliases;
+use crate::jlc::quantity::Unit;
 use crate::jlc::v1::jlc_models::*;
 use crate::jlc::v1::jlc_part_finder::Component;
+use crate::jlc::v2::models::CandidateRange;
+use crate::jlc::v2::services::candidates::constrained_candidates;
+use crate::AppState;
 
 pub async fn find_resistor(
     pool: PgPool,
+    state: AppState,
     request: JLCPartRequest,
-) -> Result<(Vec<Component>, JLCValue), sqlx::Error> {
-    // get
-    let resistor_category_id: (i32,) = sqlx::query_as("SELECT id FROM categories WHERE name = 'Resistors' and subcategory_name = 'Chip Resistor - Surface Mount'")
-    .fetch_one(&pool).await?;
+    range: CandidateRange,
+) -> Result<(Vec<Component>, JLCValue), PartError> {
+    // value conversion, resistances are searched in ohm
+    let (ohm_value, ohm_min, ohm_max) = request
+        .value
+        .stored(Unit::Ohm)
+        .ok_or_else(|| PartError::UnknownUnit(request.value.unit.clone()))?;
 
-    // value conversion
-    let ohm_string = request.value.unit.to_string();
-    let (ohm_value, ohm_max, ohm_min, _ohm_multiplier) = match ohm_string.as_str() {
-        "pΩ" | "picoohm" => (
-            request.value.nominal * 1e-12,
-            request.value.max_val * 1e-12,
-            request.value.min_val * 1e-12,
-            1e-12,
-        ),
-        "nΩ" | "nanoohm" => (
-            request.value.nominal * 1e-9,
-            request.value.max_val * 1e-9,
-            request.value.min_val * 1e-9,
-            1e-9,
-        ),
-        "μΩ" | "uΩ" | "microohm" => (
-            request.value.nominal * 1e-6,
-            request.value.max_val * 1e-6,
-            request.value.min_val * 1e-6,
-            1e-6,
-        ),
-        "mΩ" | "milliohm" => (
-            request.value.nominal * 1e-3,
-            request.value.max_val * 1e-3,
-            request.value.min_val * 1e-3,
-            1e-3,
-        ),
-        "kΩ" | "kiloohm" => (
-            request.value.nominal * 1e3,
-            request.value.max_val * 1e3,
-            request.value.min_val * 1e3,
-            1e3,
-        ),
-        "MΩ" | "megaohm" => (
-            request.value.nominal * 1e6,
-            request.value.max_val * 1e6,
-            request.value.min_val * 1e6,
-            1e6,
-        ),
-        "Ω" | "ohm" => (
-            request.value.nominal,
-            request.value.max_val,
-            request.value.min_val,
-            1.0,
-        ),
-        _ => return Err(sqlx::Error::RowNotFound),
-    };
+    let jlc_ohm_value = JLCValue::from_stored(Unit::Ohm, ohm_min, ohm_max, ohm_value);
 
-    let jlc_ohm_value = JLCValue {
-        unit: "Ω".to_string(),
-        min_val: ohm_min * 1e-12,
-        max_val: ohm_max * 1e-12,
-        nominal: ohm_value * 1e-12,
+    // the same filters as v2, a package matches any of its aliases and parts that cannot
+    // cover the ordered quantity are excluded
+    let constraints = SearchConstraints {
+        category_id: ValueColumn::Resistance.category_id(&pool).await?,
+        column: ValueColumn::Resistance,
+        nominal: ohm_value,
+        min_value: ohm_min,
+        max_value: ohm_max,
+        packages: optional_package_aliases(&request.package),
+        min_stock: request.order.required_stock(state.stock_safety_margin)?,
+        preferred_parts: state.preferred_parts(&None),
+        ..Default::default()
     };
 
-    // if request.package is not None, filter components_df on package = request.package
-    if request.package.is_some() {
-        let matching_parts: Vec<Component> = sqlx::query_as!(
-            Component,
-            r#"SELECT id as "id!", lcsc as "lcsc!", category_id as "category_id!", mfr as "mfr?", package as "package?", joints as "joints!", manufacturer as "manufacturer!", basic as "basic!", description as "description?", datasheet as "datasheet?", stock as "stock!", price as "price?", last_update as "last_update!", resistance as "resistance?", inductance, capacitance, dielectric as "dielectric?", current, voltage FROM parts WHERE category_id = $1 and resistance between $2 and $3 and package = $4 ORDER BY basic DESC LIMIT 100"#,
-            resistor_category_id.0,
-            ohm_min,
-            ohm_max,
-            request.package.unwrap()
-        ).fetch_all(&pool).await?;
-        return Ok((matching_parts, jlc_ohm_value));
-    } else {
-        let matching_parts: Vec<Component> = sqlx::query_as!(
-            Component,
-            r#"SELECT id as "id!", lcsc as "lcsc!", category_id as "category_id!", mfr as "mfr?", package as "package?", joints as "joints!", manufacturer as "manufacturer!", basic as "basic!", description as "description?", datasheet as "datasheet?", stock as "stock!", price as "price?", last_update as "last_update!", resistance as "resistance?", inductance, capacitance, dielectric as "dielectric?", current, voltage FROM parts WHERE category_id = $1 and resistance between $2 and $3 ORDER BY basic DESC LIMIT 100"#,
-            resistor_category_id.0,
-            ohm_min,
-            ohm_max
-        ).fetch_all(&pool).await?;
-        return Ok((matching_parts, jlc_ohm_value));
-    }
+    // every matching part is ranked before the best candidates are fetched,
+    // a search without matches names the eliminating constraint
+    let order_quantity = request.order.order_quantity()?;
+    let results =
+        constrained_candidates(&pool, &constraints, &request.ranking, order_quantity, range)
+            .await?;
+    Ok((results.components, jlc_ohm_value))
 }
@@ -1,3 +1,4 @@
 pub mod jlc_find_capacitor;
 pub mod jlc_find_inductor;
+pub mod jlc_find_mpn;
 pub mod jlc_find_resistor;
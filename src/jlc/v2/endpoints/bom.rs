@@ -0,0 +1,46 @@
+use axum::response::IntoResponse;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::Json;
+use axum::response::Response;
+use axum::Extension;
+use sqlx::PgPool;
+
+use crate::jlc::error::PartError;
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::bom::*;
+use crate::AppState;
+
+use self::bom::{BomRequest, BomResponse};
+
+/// Maximum number of items in a single BOM request
+const MAX_BOM_ITEMS: usize = 1000;
+
+/// JLC BOM Request, resolves v1 and v2 part requests in one round trip
+#[utoipa::path(post, path = "/jlc/v2/bom",
+request_body = BomRequest,
+responses(
+    (status = 200, description = "BOM resolved, items without a part carry an error", body = BomResponse),
+    (status = 400, description = "Bad Request", body = [NoPartFound])
+)
+)]
+pub async fn bom_request(
+    Extension(pool): Extension<PgPool>,
+    State(state): State<AppState>,
+    Json(payload): Json<BomRequest>,
+) -> (StatusCode, Response) {
+    // validate the request, the items themselves are validated one by one
+    if payload.items.len() > MAX_BOM_ITEMS {
+        return PartError::InvalidRequest(format!(
+            "At most {} items per BOM request",
+            MAX_BOM_ITEMS
+        ))
+        .response();
+    }
+
+    (
+        StatusCode::OK,
+        Json(resolve_bom(pool, state, payload).await).into_response(),
+    )
+}
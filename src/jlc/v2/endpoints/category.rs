@@ -0,0 +1,53 @@
+use axum::response::IntoResponse;
+
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::Json;
+use axum::response::Response;
+use axum::Extension;
+use sqlx::PgPool;
+
+use crate::jlc::error::PartError;
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::category::*;
+
+use self::category::{Category, Subcategory, SubcategoryQuery};
+
+/// List all JLC categories with part counts
+#[utoipa::path(get, path = "/jlc/v2/categories",
+responses(
+    (status = 200, description = "JLC Categories", body = [Category]),
+    (status = 500, description = "Internal Server Error", body = [NoPartFound])
+)
+)]
+pub async fn categories_request(Extension(pool): Extension<PgPool>) -> (StatusCode, Response) {
+    match list_categories(pool).await {
+        Ok(categories) => (StatusCode::OK, Json(categories).into_response()),
+        Err(error) => PartError::from(error).response(),
+    }
+}
+
+/// List the JLC subcategories with part counts, optionally of a single category
+#[utoipa::path(get, path = "/jlc/v2/categories/subcategories",
+params(SubcategoryQuery),
+responses(
+    (status = 200, description = "JLC Subcategories", body = [Subcategory]),
+    (status = 404, description = "JLC Category Not Found", body = [NoPartFound]),
+    (status = 500, description = "Internal Server Error", body = [NoPartFound])
+)
+)]
+pub async fn subcategories_request(
+    Extension(pool): Extension<PgPool>,
+    Query(query): Query<SubcategoryQuery>,
+) -> (StatusCode, Response) {
+    let subcategories = match list_subcategories(pool, query.category).await {
+        Ok(subcategories) => subcategories,
+        Err(error) => return PartError::from(error).response(),
+    };
+
+    // an unknown category has no subcategories
+    if subcategories.is_empty() {
+        return PartError::NotFound("No category found".to_string()).response();
+    }
+    (StatusCode::OK, Json(subcategories).into_response())
+}
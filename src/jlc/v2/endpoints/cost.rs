@@ -0,0 +1,60 @@
+use axum::response::IntoResponse;
+
+use axum::http::StatusCode;
+use axum::response::Json;
+use axum::response::Response;
+use axum::Extension;
+use sqlx::PgPool;
+
+use crate::jlc::error::PartError;
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::cost::*;
+
+use self::cost::{CostEstimate, CostEstimateRequest};
+
+/// Maximum number of parts in a single cost estimate
+const MAX_COST_ITEMS: usize = 1000;
+
+/// JLC BOM Cost Estimate, part cost and assembly fees of resolved parts
+#[utoipa::path(post, path = "/jlc/v2/bom/cost",
+request_body = CostEstimateRequest,
+responses(
+    (status = 200, description = "Cost estimated, unknown part numbers are listed as missing", body = CostEstimate),
+    (status = 400, description = "Bad Request", body = [NoPartFound])
+)
+)]
+pub async fn cost_request(
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<CostEstimateRequest>,
+) -> (StatusCode, Response) {
+    // validate the request
+    if let Err(message) = validate_request(&payload) {
+        return PartError::InvalidRequest(message).response();
+    }
+
+    match estimate_bom_cost(pool, payload).await {
+        Ok(estimate) => (StatusCode::OK, Json(estimate).into_response()),
+        Err(error) => error.response(),
+    }
+}
+
+/// Validate the quantities and fees of a cost estimate request
+fn validate_request(payload: &CostEstimateRequest) -> Result<(), String> {
+    if payload.parts.len() > MAX_COST_ITEMS {
+        return Err(format!("At most {} parts per request", MAX_COST_ITEMS));
+    }
+    if payload.boards.is_some_and(|boards| boards < 1) {
+        return Err("boards must be at least 1".to_string());
+    }
+    if payload.parts.iter().any(|item| item.quantity < 0) {
+        return Err("quantity must not be negative".to_string());
+    }
+    if payload
+        .fees
+        .as_ref()
+        .is_some_and(|fees| fees.extended_part_fee < 0.0 || fees.basic_part_fee < 0.0)
+    {
+        return Err("Fees must not be negative".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use axum::response::IntoResponse;
+
+use axum::extract::Query;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::Json;
+use axum::response::Response;
+use axum::Extension;
+use sqlx::PgPool;
+
+use crate::jlc::error::PartError;
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::keyword::*;
+use crate::AppState;
+
+use self::keyword::KeywordSearchRequest;
+
+/// JLC Keyword Search over part descriptions, manufacturers and part numbers
+#[utoipa::path(post, path = "/jlc/v2/search/keyword",
+request_body = KeywordSearchRequest,
+params(Pagination),
+responses(
+    (status = 200, description = "JLC Parts Found", body = ComponentPage),
+    (status = 400, description = "Bad Request", body = [NoPartFound]),
+    (status = 404, description = "JLC Part Not Found", body = [NoPartFound]),
+    (status = 500, description = "Internal Server Error", body = [NoPartFound])
+)
+)]
+pub async fn keyword_request(
+    Extension(pool): Extension<PgPool>,
+    State(state): State<AppState>,
+    Query(pagination): Query<Pagination>,
+    Json(payload): Json<KeywordSearchRequest>,
+) -> (StatusCode, Response) {
+    // validate the request
+    if let Err(message) = validate_request(&payload) {
+        return PartError::InvalidRequest(message).response();
+    }
+
+    // all is well, let's find the parts
+    let results = match search_keywords(pool, state, payload, pagination.range()).await {
+        Ok(results) => results,
+        Err(error) => return PartError::from(error).response(),
+    };
+
+    // if no part matches, return a 404
+    if results.components.is_empty() {
+        return PartError::NoMatch { constraint: None }.response();
+    }
+    // return the requested page of the parts, most relevant first
+    (
+        StatusCode::OK,
+        Json(pagination.paginate(results)).into_response(),
+    )
+}
+
+/// Validate the query of a keyword search request
+fn validate_request(payload: &KeywordSearchRequest) -> Result<(), String> {
+    if payload.query.trim().is_empty() {
+        return Err("query must not be empty".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use axum::response::IntoResponse;
+
+use axum::extract::Query;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::Json;
+use axum::response::Response;
+use axum::Extension;
+use sqlx::PgPool;
+
+use crate::jlc::error::PartError;
+use crate::jlc::mpn::{normalize_mpn, MpnMatch};
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::mpn::*;
+use crate::AppState;
+
+use self::mpn::MpnSearchRequest;
+
+/// JLC Part Search by manufacturer part number, exact, prefix or fuzzy
+#[utoipa::path(post, path = "/jlc/v2/mpn",
+request_body = MpnSearchRequest,
+params(Pagination),
+responses(
+    (status = 200, description = "JLC Parts Found", body = ComponentPage),
+    (status = 400, description = "Bad Request", body = [NoPartFound]),
+    (status = 404, description = "JLC Part Not Found", body = [NoPartFound]),
+    (status = 500, description = "Internal Server Error", body = [NoPartFound])
+)
+)]
+pub async fn mpn_request(
+    Extension(pool): Extension<PgPool>,
+    State(state): State<AppState>,
+    Query(pagination): Query<Pagination>,
+    Json(payload): Json<MpnSearchRequest>,
+) -> (StatusCode, Response) {
+    // validate the request
+    if let Err(message) = validate_request(&payload) {
+        return PartError::InvalidRequest(message).response();
+    }
+
+    // all is well, let's find the parts
+    let results = match find_mpn(pool, state, payload, pagination.range()).await {
+        Ok(results) => results,
+        Err(error) => return PartError::from(error).response(),
+    };
+
+    // if no part matches, return a 404
+    if results.components.is_empty() {
+        return PartError::NoMatch { constraint: None }.response();
+    }
+    // return the requested page of the ranked parts
+    (
+        StatusCode::OK,
+        Json(pagination.paginate(results)).into_response(),
+    )
+}
+
+/// Longest part number accepted, the edit distance of fuzzy matches is limited to 255 characters
+const MAX_MPN_LENGTH: usize = 64;
+
+/// Validate the part number of an mpn search request
+fn validate_request(payload: &MpnSearchRequest) -> Result<(), String> {
+    // short prefixes and fuzzy patterns match a large part of the parts table
+    let length = normalize_mpn(&payload.mpn).chars().count();
+    if length == 0 {
+        return Err("mpn must not be empty".to_string());
+    }
+    if length > MAX_MPN_LENGTH {
+        return Err(format!(
+            "mpn must be at most {MAX_MPN_LENGTH} characters long"
+        ));
+    }
+    if payload
+        .match_mode
+        .is_some_and(|match_mode| match_mode != MpnMatch::Exact)
+        && length < 3
+    {
+        return Err(
+            "mpn must be at least 3 characters long for prefix and fuzzy matches".to_string(),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use axum::response::IntoResponse;
+
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::Json;
+use axum::response::Response;
+use axum::Extension;
+use sqlx::PgPool;
+
+use crate::jlc::error::PartError;
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::package::*;
+
+use self::package::{Package, PackageQuery};
+
+/// List the packages available in a category with part counts
+#[utoipa::path(get, path = "/jlc/v2/packages",
+params(PackageQuery),
+responses(
+    (status = 200, description = "JLC Packages", body = [Package]),
+    (status = 500, description = "Internal Server Error", body = [NoPartFound])
+)
+)]
+pub async fn packages_request(
+    Extension(pool): Extension<PgPool>,
+    Query(query): Query<PackageQuery>,
+) -> (StatusCode, Response) {
+    match list_packages(pool, query).await {
+        Ok(packages) => (StatusCode::OK, Json(packages).into_response()),
+        Err(error) => PartError::from(error).response(),
+    }
+}
@@ -0,0 +1,65 @@
+use axum::response::IntoResponse;
+
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::Json;
+use axum::response::Response;
+use axum::Extension;
+use sqlx::PgPool;
+
+use crate::jlc::error::PartError;
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::part::*;
+
+use self::part::{PartBatchRequest, PartBatchResponse, PartRecord};
+
+/// Maximum number of LCSC part numbers in a single batch request
+const MAX_BATCH_SIZE: usize = 1000;
+
+/// JLC Part Lookup by LCSC part number
+#[utoipa::path(get, path = "/jlc/v2/parts/{lcsc}",
+params(("lcsc" = String, Path, description = "LCSC part number, e.g. C25804")),
+responses(
+    (status = 200, description = "JLC Part Found", body = PartRecord),
+    (status = 404, description = "JLC Part Not Found", body = [NoPartFound])
+)
+)]
+pub async fn part_request(
+    Extension(pool): Extension<PgPool>,
+    Path(lcsc): Path<String>,
+) -> (StatusCode, Response) {
+    match find_part_by_lcsc(pool, lcsc.clone()).await {
+        Ok(Some(part)) => (StatusCode::OK, Json(part).into_response()),
+        Ok(None) => {
+            PartError::NotFound(format!("No part found with LCSC part number {}", lcsc)).response()
+        }
+        Err(error) => PartError::from(error).response(),
+    }
+}
+
+/// JLC Part Batch Lookup by LCSC part numbers
+#[utoipa::path(post, path = "/jlc/v2/parts",
+request_body = PartBatchRequest,
+responses(
+    (status = 200, description = "JLC Parts Found, unknown part numbers are listed as missing", body = PartBatchResponse),
+    (status = 400, description = "Bad Request", body = [NoPartFound])
+)
+)]
+pub async fn batch_request(
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<PartBatchRequest>,
+) -> (StatusCode, Response) {
+    // validate the request
+    if payload.lcsc.len() > MAX_BATCH_SIZE {
+        return PartError::InvalidRequest(format!(
+            "At most {} LCSC part numbers per request",
+            MAX_BATCH_SIZE
+        ))
+        .response();
+    }
+
+    match find_parts_by_lcsc(pool, payload.lcsc).await {
+        Ok(response) => (StatusCode::OK, Json(response).into_response()),
+        Err(error) => PartError::from(error).response(),
+    }
+}
@@ -1,13 +1,17 @@
 use axum::response::IntoResponse;
 
+use axum::extract::Query;
+use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::Json;
 use axum::response::Response;
 use axum::Extension;
 use sqlx::PgPool;
 
+use crate::jlc::constraints::SearchDiagnostics;
 use crate::jlc::v2::models::*;
 use crate::jlc::v2::services::resistor::*;
+use crate::AppState;
 
 use self::resistor::ResistorRequest;
 
@@ -17,98 +21,77 @@ request_body = ResistorRequest,
 responses(
     (status = 200, description = "JLC Part Found", body = [Component]),
     (status = 400, description = "Bad Request", body = [NoPartFound]),
-    (status = 404, description = "JLC Part Not Found", body = [NoPartFound])
+    (status = 404, description = "JLC Part Not Found", body = [NoPartFound]),
+    (status = 500, description = "Internal Server Error", body = [NoPartFound])
 )
 )]
 pub async fn part_request(
     // this argument tells axum to parse the request body
     // as JSON into a `CreateUser` type
     Extension(pool): Extension<PgPool>,
+    State(state): State<AppState>,
     Json(payload): Json<ResistorRequest>,
 ) -> (StatusCode, Response) {
-    // validate the request
-    if payload.tolerance_percentage.is_some() && payload.tolerance_percentage < Some(0.0)
-        || payload.tolerance_percentage > Some(100.0)
-    {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(NoPartFound {
-                code: 400,
-                message: "Tolerance percentage must be between 0 and 100".to_string(),
-            })
-            .into_response(),
-        );
-    }
-    // validate that either absolute_tolerance and tolerance_percentage is set, not both and not neither
-    if payload.absolute_tolerance.is_some() && payload.tolerance_percentage.is_some() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(NoPartFound {
-                code: 400,
-                message: "Either absolute_tolerance or tolerance_percentage must be set, not both"
-                    .to_string(),
-            })
-            .into_response(),
-        );
-    }
-    if payload.absolute_tolerance.is_none() && payload.tolerance_percentage.is_none() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(NoPartFound {
-                code: 400,
-                message:
-                    "Either absolute_tolerance or tolerance_percentage must be set, not neither"
-                        .to_string(),
-            })
-            .into_response(),
-        );
-    }
-    // validate that if absolute_tolerance is set, absolute_tolerance_unit is also set
-    if payload.absolute_tolerance.is_some() && payload.absolute_tolerance_unit.is_none() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(NoPartFound {
-                code: 400,
-                message: "If absolute_tolerance is set, absolute_tolerance_unit must also be set"
-                    .to_string(),
-            })
-            .into_response(),
-        );
-    }
+    // an invalid request is rejected, a search without matches names the eliminating constraint
+    let results = match find_resistor(pool, state, payload, CandidateRange::Best(1)).await {
+        Ok(results) => results,
+        Err(error) => return error.response(),
+    };
 
-    // all is well, let's find the part
-    let result_vec_component = find_resistor(pool, payload).await;
+    // return the first element of the vector
+    (
+        StatusCode::OK,
+        Json(results.components.first().unwrap()).into_response(),
+    )
+}
 
-    if result_vec_component.is_err() {
-        return (
-            StatusCode::NOT_FOUND,
-            Json(NoPartFound {
-                code: 500,
-                message: format!(
-                    "Internal Server Error: {}",
-                    result_vec_component.unwrap_err()
-                ),
-            })
-            .into_response(),
-        );
-    }
+/// JLC Resistor Candidates Request, a ranked and paginated list of matching parts
+#[utoipa::path(post, path = "/jlc/v2/resistor/candidates",
+request_body = ResistorRequest,
+params(Pagination),
+responses(
+    (status = 200, description = "JLC Parts Found", body = ComponentPage),
+    (status = 400, description = "Bad Request", body = [NoPartFound]),
+    (status = 404, description = "JLC Part Not Found", body = [NoPartFound]),
+    (status = 500, description = "Internal Server Error", body = [NoPartFound])
+)
+)]
+pub async fn candidates_request(
+    Extension(pool): Extension<PgPool>,
+    State(state): State<AppState>,
+    Query(pagination): Query<Pagination>,
+    Json(payload): Json<ResistorRequest>,
+) -> (StatusCode, Response) {
+    // an invalid request is rejected, a search without matches names the eliminating constraint
+    let results = match find_resistor(pool, state, payload, pagination.range()).await {
+        Ok(results) => results,
+        Err(error) => return error.response(),
+    };
 
-    // unwrap the result and convert it into a JSON response
-    // if the length of the vector is 0, return a 404
-    let vec_component = result_vec_component.unwrap();
-    if vec_component.len() == 0 {
-        return (
-            StatusCode::NOT_FOUND,
-            Json(NoPartFound {
-                code: 404,
-                message: "No part found".to_string(),
-            })
-            .into_response(),
-        );
-    }
-    // return the first element of the vector
+    // return the requested page of the ranked candidates
     (
         StatusCode::OK,
-        Json(vec_component.first().unwrap()).into_response(),
+        Json(pagination.paginate(results)).into_response(),
     )
 }
+
+/// JLC Resistor Diagnostics Request, the number of parts left after each filter of the request
+/// and the nearest relaxations when no part is left
+#[utoipa::path(post, path = "/jlc/v2/resistor/diagnostics",
+request_body = ResistorRequest,
+responses(
+    (status = 200, description = "JLC Search Diagnostics", body = SearchDiagnostics),
+    (status = 400, description = "Bad Request", body = [NoPartFound]),
+    (status = 500, description = "Internal Server Error", body = [NoPartFound])
+)
+)]
+pub async fn diagnostics_request(
+    Extension(pool): Extension<PgPool>,
+    State(state): State<AppState>,
+    Json(payload): Json<ResistorRequest>,
+) -> (StatusCode, Response) {
+    match diagnose_resistor(pool, state, payload).await {
+        Ok(diagnostics) => (StatusCode::OK, Json(diagnostics).into_response()),
+        Err(error) => error.response(),
+    }
+}
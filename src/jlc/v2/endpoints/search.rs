@@ -0,0 +1,77 @@
+use axum::response::IntoResponse;
+
+use axum::extract::Query;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::Json;
+use axum::response::Response;
+use axum::Extension;
+use sqlx::PgPool;
+
+use crate::jlc::error::PartError;
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::search::*;
+use crate::AppState;
+
+use self::search::PartSearchRequest;
+
+/// JLC Parametric Part Search over any category
+#[utoipa::path(post, path = "/jlc/v2/search",
+request_body = PartSearchRequest,
+params(Pagination),
+responses(
+    (status = 200, description = "JLC Parts Found", body = ComponentPage),
+    (status = 400, description = "Bad Request", body = [NoPartFound]),
+    (status = 404, description = "JLC Part Not Found", body = [NoPartFound]),
+    (status = 500, description = "Internal Server Error", body = [NoPartFound])
+)
+)]
+pub async fn search_request(
+    Extension(pool): Extension<PgPool>,
+    State(state): State<AppState>,
+    Query(pagination): Query<Pagination>,
+    Json(payload): Json<PartSearchRequest>,
+) -> (StatusCode, Response) {
+    // validate the request
+    if let Err(message) = validate_request(&payload) {
+        return PartError::InvalidRequest(message).response();
+    }
+
+    // all is well, let's find the parts
+    let results = match search_parts(pool, state, payload, pagination.range()).await {
+        Ok(results) => results,
+        Err(error) => return error.response(),
+    };
+
+    // if no part matches, return a 404
+    if results.components.is_empty() {
+        return PartError::NoMatch { constraint: None }.response();
+    }
+    // return the requested page of the ranked parts
+    (
+        StatusCode::OK,
+        Json(pagination.paginate(results)).into_response(),
+    )
+}
+
+/// Validate the filter fields of a search request
+fn validate_request(payload: &PartSearchRequest) -> Result<(), String> {
+    // searching the whole parts table is too expensive, require a category
+    if payload.category.is_none() && payload.subcategory.is_none() && payload.category_id.is_none()
+    {
+        return Err("Either category, subcategory or category_id must be set".to_string());
+    }
+    if let (Some(min_voltage), Some(max_voltage)) = (payload.min_voltage, payload.max_voltage) {
+        if min_voltage > max_voltage {
+            return Err("min_voltage must be smaller than or equal to max_voltage".to_string());
+        }
+    }
+    if let (Some(min_current), Some(max_current)) = (payload.min_current, payload.max_current) {
+        if min_current > max_current {
+            return Err("min_current must be smaller than or equal to max_current".to_string());
+        }
+    }
+    // validate the stock requirements
+    payload.order.validate()?;
+    Ok(())
+}
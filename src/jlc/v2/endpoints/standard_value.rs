@@ -0,0 +1,32 @@
+use axum::response::IntoResponse;
+
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::Json;
+use axum::response::Response;
+use axum::Extension;
+use sqlx::PgPool;
+
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::standard_value::*;
+
+use self::standard_value::{StandardValueQuery, StandardValues};
+
+/// List the values of an E-series in a range that are stocked in a package, with part counts
+#[utoipa::path(get, path = "/jlc/v2/standard-values",
+params(StandardValueQuery),
+responses(
+    (status = 200, description = "JLC Standard Values", body = StandardValues),
+    (status = 400, description = "Bad Request", body = [NoPartFound]),
+    (status = 500, description = "Internal Server Error", body = [NoPartFound])
+)
+)]
+pub async fn standard_values_request(
+    Extension(pool): Extension<PgPool>,
+    Query(query): Query<StandardValueQuery>,
+) -> (StatusCode, Response) {
+    match list_standard_values(pool, query).await {
+        Ok(standard_values) => (StatusCode::OK, Json(standard_values).into_response()),
+        Err(error) => error.response(),
+    }
+}
@@ -2,17 +2,52 @@ pub mod endpoints;
 pub mod models;
 pub mod services;
 
-use axum::routing::post;
+use axum::routing::{get, post};
 use axum::Router;
 
+use crate::jlc::v2::endpoints::bom;
 use crate::jlc::v2::endpoints::capacitor;
+use crate::jlc::v2::endpoints::category;
+use crate::jlc::v2::endpoints::cost;
 use crate::jlc::v2::endpoints::inductor;
+use crate::jlc::v2::endpoints::keyword;
+use crate::jlc::v2::endpoints::mpn;
+use crate::jlc::v2::endpoints::package;
+use crate::jlc::v2::endpoints::part;
 use crate::jlc::v2::endpoints::resistor;
+use crate::jlc::v2::endpoints::search;
+use crate::jlc::v2::endpoints::standard_value;
 use crate::AppState;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/resistor", post(resistor::part_request))
+        .route("/resistor/candidates", post(resistor::candidates_request))
+        .route("/resistor/diagnostics", post(resistor::diagnostics_request))
         .route("/capacitor", post(capacitor::part_request))
+        .route("/capacitor/candidates", post(capacitor::candidates_request))
+        .route(
+            "/capacitor/diagnostics",
+            post(capacitor::diagnostics_request),
+        )
         .route("/inductor", post(inductor::part_request))
+        .route("/inductor/candidates", post(inductor::candidates_request))
+        .route("/inductor/diagnostics", post(inductor::diagnostics_request))
+        .route("/bom", post(bom::bom_request))
+        .route("/bom/cost", post(cost::cost_request))
+        .route("/search", post(search::search_request))
+        .route("/search/keyword", post(keyword::keyword_request))
+        .route("/categories", get(category::categories_request))
+        .route(
+            "/categories/subcategories",
+            get(category::subcategories_request),
+        )
+        .route("/mpn", post(mpn::mpn_request))
+        .route("/packages", get(package::packages_request))
+        .route(
+            "/standard-values",
+            get(standard_value::standard_values_request),
+        )
+        .route("/parts", post(part::batch_request))
+        .route("/parts/{lcsc}", get(part::part_request))
 }
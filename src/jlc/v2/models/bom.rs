@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::jlc::v1::jlc_models::{BestComponent, JLCPartRequest};
+use crate::jlc::v2::models::capacitor::CapacitorRequest;
+use crate::jlc::v2::models::inductor::InductorRequest;
+use crate::jlc::v2::models::resistor::ResistorRequest;
+use crate::jlc::v2::models::{Component, NoPartFound};
+
+/// A part request of any API version, tagged by `kind`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BomPartRequest {
+    V1(JLCPartRequest),
+    Resistor(ResistorRequest),
+    Capacitor(CapacitorRequest),
+    Inductor(InductorRequest),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BomItemRequest {
+    /// Designator of the part on the board, e.g. `R1`
+    pub designator: String,
+    pub part: BomPartRequest,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BomRequest {
+    pub items: Vec<BomItemRequest>,
+    /// Prefer the same LCSC part for compatible items and basic parts over extended
+    /// parts, instead of picking the best part for every item independently
+    pub consolidate: Option<bool>,
+}
+
+/// The best part for a BOM item, in the response format of the requested API version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum BomPart {
+    V1(BestComponent),
+    V2(Component),
+}
+
+impl BomPart {
+    pub fn lcsc(&self) -> &str {
+        match self {
+            BomPart::V1(component) => &component.lcsc_id,
+            BomPart::V2(component) => &component.lcsc,
+        }
+    }
+}
+
+/// Result of a single BOM item, either the best part or the reason no part was found
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BomItemResult {
+    pub designator: String,
+    pub part: Option<BomPart>,
+    pub error: Option<NoPartFound>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BomResponse {
+    /// Results in request order
+    pub items: Vec<BomItemResult>,
+    /// Number of distinct LCSC parts used by the BOM
+    pub unique_parts: i64,
+    /// Number of distinct extended LCSC parts used by the BOM, each one adds a feeder fee
+    pub extended_parts: i64,
+}
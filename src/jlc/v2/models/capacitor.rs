@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
+use crate::jlc::e_series::ESeries;
+use crate::jlc::quantity::{prefix_multiplier, request_quantity, Quantity, Unit, ValueFields};
+use crate::jlc::ranking::RankingOptions;
+use crate::jlc::stock::OrderOptions;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 pub enum CapacitorUnit {
     PicoFarad,
@@ -12,12 +17,121 @@ pub enum CapacitorUnit {
     MegaFarad,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+impl CapacitorUnit {
+    /// SI prefix of the unit
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            CapacitorUnit::PicoFarad => "p",
+            CapacitorUnit::NanoFarad => "n",
+            CapacitorUnit::MicroFarad => "u",
+            CapacitorUnit::MilliFarad => "m",
+            CapacitorUnit::Farad => "",
+            CapacitorUnit::KiloFarad => "k",
+            CapacitorUnit::MegaFarad => "M",
+        }
+    }
+
+    /// Multiplier from the unit to farad
+    pub fn multiplier(&self) -> f64 {
+        prefix_multiplier(self.prefix()).unwrap_or(1.0)
+    }
+}
+
+/// Ceramic dielectric class of an MLCC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum Dielectric {
+    #[serde(alias = "NP0")]
+    C0G,
+    X5R,
+    X6S,
+    X7R,
+    X7S,
+    X8R,
+    Y5V,
+}
+
+impl Dielectric {
+    /// Spellings of this dielectric as stored in the parts table
+    pub fn names(&self) -> &'static [&'static str] {
+        match self {
+            Dielectric::C0G => &["C0G", "NP0", "COG", "NPO"],
+            Dielectric::X5R => &["X5R"],
+            Dielectric::X6S => &["X6S"],
+            Dielectric::X7R => &["X7R"],
+            Dielectric::X7S => &["X7S"],
+            Dielectric::X8R => &["X8R"],
+            Dielectric::Y5V => &["Y5V"],
+        }
+    }
+}
+
+/// Expand a list of allowed dielectrics into the stored spellings, for use in `dielectric = ANY($n)`
+pub fn dielectric_names(dielectrics: &Option<Vec<Dielectric>>) -> Option<Vec<String>> {
+    dielectrics.as_ref().map(|dielectrics| {
+        dielectrics
+            .iter()
+            .flat_map(|dielectric| dielectric.names())
+            .map(|name| name.to_string())
+            .collect()
+    })
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
 pub struct CapacitorRequest {
-    pub unit: CapacitorUnit,
-    pub value: f64,
+    pub unit: Option<CapacitorUnit>,
+    pub value: Option<f64>,
+    /// Value with unit and optional inline tolerance as a single string, e.g. "100nF ±10%",
+    /// replaces `value`, `unit` and, when a tolerance is given, the tolerance fields
+    pub value_string: Option<String>,
+    /// E-series the nominal value is snapped to before searching, the tolerance is applied
+    /// around the snapped value, e.g. E6 searches 4.3nF as 4.7nF
+    pub e_series: Option<ESeries>,
     pub package: Option<String>,
+    /// Allowed packages, combined with `package`
+    pub packages: Option<Vec<String>>,
+    /// Largest allowed chip package as an imperial size code, e.g. "0603"
+    pub max_package_size: Option<String>,
     pub tolerance_percentage: Option<f64>,
     pub absolute_tolerance: Option<f64>,
     pub absolute_tolerance_unit: Option<CapacitorUnit>,
+    /// Minimum rated voltage in volt
+    pub min_voltage: Option<f64>,
+    /// Maximum rated voltage in volt
+    pub max_voltage: Option<f64>,
+    /// Allowed dielectric classes, any dielectric is accepted when omitted
+    pub dielectrics: Option<Vec<Dielectric>>,
+    #[serde(flatten)]
+    pub order: OrderOptions,
+    /// Only return basic parts and preferred extended parts
+    pub basic_only: Option<bool>,
+    /// LCSC part numbers of extended parts that are ranked and filtered as basic parts,
+    /// in addition to the preferred parts configured on the server
+    pub preferred_parts: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub ranking: RankingOptions,
+}
+
+impl CapacitorRequest {
+    /// The requested value in farad with its tolerance, from `value_string`
+    /// or from the separate value, unit and tolerance fields, snapped to `e_series` when set
+    pub fn quantity(&self) -> Result<Quantity, String> {
+        let mut quantity = request_quantity(
+            ValueFields {
+                value_string: self.value_string.as_deref(),
+                value: self.value,
+                multiplier: self.unit.as_ref().map(CapacitorUnit::multiplier),
+                tolerance_percentage: self.tolerance_percentage,
+                absolute_tolerance: self.absolute_tolerance,
+                absolute_tolerance_multiplier: self
+                    .absolute_tolerance_unit
+                    .as_ref()
+                    .map(CapacitorUnit::multiplier),
+            },
+            Unit::Farad,
+        )?;
+        if let Some(series) = self.e_series {
+            quantity.value = series.snap(quantity.value);
+        }
+        Ok(quantity)
+    }
 }
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// A JLC category with the part counts of all its subcategories
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Category {
+    pub name: String,
+    pub subcategory_count: i64,
+    pub part_count: i64,
+    pub basic_count: i64,
+    pub in_stock_count: i64,
+}
+
+/// A row of the categories table, its id can be used as `category_id` in the part search
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Subcategory {
+    pub id: i64,
+    pub name: String,
+    pub subcategory_name: String,
+    pub part_count: i64,
+    pub basic_count: i64,
+    pub in_stock_count: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct SubcategoryQuery {
+    /// Only list the subcategories of this category
+    pub category: Option<String>,
+}
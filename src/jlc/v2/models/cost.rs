@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CostItem {
+    /// LCSC part number, e.g. "C25804"
+    pub lcsc: String,
+    /// Number of placements on a single board
+    pub quantity: i64,
+}
+
+/// Assembly fees in USD, charged once per order for every unique part
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct AssemblyFees {
+    /// Feeder loading fee of an extended part, defaults to 3.0
+    pub extended_part_fee: f64,
+    /// Fee of a basic part, defaults to 0.0
+    pub basic_part_fee: f64,
+}
+
+impl AssemblyFees {
+    pub const DEFAULT_EXTENDED_PART_FEE: f64 = 3.0;
+    pub const DEFAULT_BASIC_PART_FEE: f64 = 0.0;
+}
+
+impl Default for AssemblyFees {
+    fn default() -> Self {
+        AssemblyFees {
+            extended_part_fee: Self::DEFAULT_EXTENDED_PART_FEE,
+            basic_part_fee: Self::DEFAULT_BASIC_PART_FEE,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CostEstimateRequest {
+    /// Resolved parts of a BOM, repeated LCSC part numbers are added up
+    pub parts: Vec<CostItem>,
+    /// Number of assembled boards, defaults to 1
+    pub boards: Option<i64>,
+    pub fees: Option<AssemblyFees>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CostLine {
+    pub lcsc: String,
+    pub basic: bool,
+    /// Unit price in USD at the ordered quantity, parts without a known price are not included in the part cost
+    pub unit_price: Option<f64>,
+    /// Number of placements over all boards
+    pub quantity: i64,
+    pub part_cost: f64,
+    pub fee: f64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CostEstimate {
+    pub boards: i64,
+    /// Cost per unique part, in request order
+    pub lines: Vec<CostLine>,
+    /// Requested LCSC part numbers that are not stored, they are not included in the cost
+    pub missing: Vec<String>,
+    /// Number of unique extended parts
+    pub extended_parts: i64,
+    /// Sum of the part costs in USD
+    pub part_cost: f64,
+    /// Sum of the assembly fees in USD
+    pub fees: f64,
+    /// Part cost plus fees in USD
+    pub total: f64,
+    /// Total divided by the number of boards in USD
+    pub total_per_board: f64,
+}
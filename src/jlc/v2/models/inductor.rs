@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
+use crate::jlc::quantity::{prefix_multiplier, request_quantity, Quantity, Unit, ValueFields};
+use crate::jlc::ranking::RankingOptions;
+use crate::jlc::stock::OrderOptions;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 pub enum InductorUnit {
     PicoHenry,
@@ -12,12 +16,75 @@ pub enum InductorUnit {
     MegaHenry,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+impl InductorUnit {
+    /// SI prefix of the unit
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            InductorUnit::PicoHenry => "p",
+            InductorUnit::NanoHenry => "n",
+            InductorUnit::MicroHenry => "u",
+            InductorUnit::MilliHenry => "m",
+            InductorUnit::Henry => "",
+            InductorUnit::KiloHenry => "k",
+            InductorUnit::MegaHenry => "M",
+        }
+    }
+
+    /// Multiplier from the unit to henry
+    pub fn multiplier(&self) -> f64 {
+        prefix_multiplier(self.prefix()).unwrap_or(1.0)
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
 pub struct InductorRequest {
-    pub unit: InductorUnit,
-    pub value: f64,
+    pub unit: Option<InductorUnit>,
+    pub value: Option<f64>,
+    /// Value with unit and optional inline tolerance as a single string, e.g. "2.2µH ±20%",
+    /// replaces `value`, `unit` and, when a tolerance is given, the tolerance fields
+    pub value_string: Option<String>,
     pub package: Option<String>,
+    /// Allowed packages, combined with `package`
+    pub packages: Option<Vec<String>>,
+    /// Largest allowed chip package as an imperial size code, e.g. "0603"
+    pub max_package_size: Option<String>,
     pub tolerance_percentage: Option<f64>,
     pub absolute_tolerance: Option<f64>,
     pub absolute_tolerance_unit: Option<InductorUnit>,
+    /// Minimum rated current in ampere
+    pub min_current: Option<f64>,
+    /// Minimum saturation current in ampere, parts without a known saturation current are excluded
+    pub min_saturation_current: Option<f64>,
+    /// Maximum DC resistance in ohm
+    pub max_dcr: Option<f64>,
+    #[serde(flatten)]
+    pub order: OrderOptions,
+    /// Only return basic parts and preferred extended parts
+    pub basic_only: Option<bool>,
+    /// LCSC part numbers of extended parts that are ranked and filtered as basic parts,
+    /// in addition to the preferred parts configured on the server
+    pub preferred_parts: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub ranking: RankingOptions,
+}
+
+impl InductorRequest {
+    /// The requested value in henry with its tolerance, from `value_string`
+    /// or from the separate value, unit and tolerance fields
+    pub fn quantity(&self) -> Result<Quantity, String> {
+        request_quantity(
+            ValueFields {
+                value_string: self.value_string.as_deref(),
+                value: self.value,
+                multiplier: self.unit.as_ref().map(InductorUnit::multiplier),
+                tolerance_percentage: self.tolerance_percentage,
+                absolute_tolerance: self.absolute_tolerance,
+                absolute_tolerance_multiplier: self
+                    .absolute_tolerance_unit
+                    .as_ref()
+                    .map(InductorUnit::multiplier),
+            },
+            Unit::Henry,
+        )
+    }
 }
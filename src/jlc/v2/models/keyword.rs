@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::jlc::ranking::RankingOptions;
+
+/// Keyword search over the description, manufacturer and manufacturer part number of every part
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct KeywordSearchRequest {
+    /// Search terms in web search syntax, e.g. `"hall effect" sensor -latch`
+    pub query: String,
+    /// Category name, e.g. `Sensors`
+    pub category: Option<String>,
+    /// Subcategory name
+    pub subcategory: Option<String>,
+    pub package: Option<String>,
+    /// Only return basic parts and preferred extended parts when true
+    pub basic_only: Option<bool>,
+    /// The parts are ordered by relevance when no ranking strategy is given
+    #[serde(flatten)]
+    pub ranking: RankingOptions,
+}
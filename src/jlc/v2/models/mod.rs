@@ -1,10 +1,23 @@
+pub mod bom;
 pub mod capacitor;
+pub mod category;
+pub mod cost;
 pub mod inductor;
+pub mod keyword;
+pub mod mpn;
+pub mod package;
+pub mod part;
 pub mod resistor;
+pub mod search;
+pub mod standard_value;
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
 use utoipa::{IntoParams, ToSchema};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+use crate::jlc::price::PriceTier;
+use crate::jlc::ranking::Rankable;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema, sqlx::FromRow)]
 pub struct Component {
     pub lcsc: String,
     pub category_id: i64,
@@ -17,12 +30,122 @@ pub struct Component {
     pub datasheet: Option<String>,
     pub stock: i64,
     pub price: Option<f64>,
+    /// Price tier at the requested quantity, `price` applies when the part has no tiers
+    #[schema(value_type = Option<PriceTier>)]
+    #[param(value_type = Option<PriceTier>)]
+    pub price_tier: Option<Json<PriceTier>>,
+    /// Unit price in USD at the requested quantity, the price the ranking and price filters use
+    pub unit_price: Option<f64>,
     pub dielectric: Option<String>,
+    pub voltage: Option<f64>,
+    pub current: Option<f64>,
+    pub resistance: Option<f64>,
+    pub capacitance: Option<f64>,
+    pub inductance: Option<f64>,
+    /// Power rating in watt, parsed from the description
+    pub power: Option<f64>,
+    /// Manufacturer tolerance in percent, parsed from the description
+    pub tolerance: Option<f64>,
+    /// Saturation current of inductors in ampere, parsed from the description
+    pub saturation_current: Option<f64>,
+    /// DC resistance of inductors in ohm, parsed from the description
+    pub dcr: Option<f64>,
+    /// Relative error of the matched value to the requested nominal value, 0.01 is 1%
+    pub relative_error: Option<f64>,
+    /// E-series the requested value was snapped to, e.g. "E24"
+    pub e_series: Option<String>,
+    /// Snapped value the search used, in the unit of the value column
+    pub e_series_value: Option<f64>,
+}
+
+impl Rankable for Component {
+    fn lcsc(&self) -> &str {
+        &self.lcsc
+    }
+    fn basic(&self) -> bool {
+        self.basic
+    }
+    fn value(&self) -> Option<f64> {
+        self.resistance.or(self.capacitance).or(self.inductance)
+    }
 }
 
 // Response No Part Found
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
 pub struct NoPartFound {
+    /// HTTP status code
     pub code: i64,
+    /// Stable error code, such as `invalid_request`, `unknown_unit`, `no_part_found` or `database_error`
+    #[serde(default)]
+    pub error: String,
     pub message: String,
+    /// Constraint that eliminated the last remaining candidates of a search without matches
+    #[serde(default)]
+    pub constraint: Option<String>,
+}
+
+/// The ranked candidates of a search, best first
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SearchResults {
+    /// Number of parts matching the search, only counted for a page of candidates
+    pub total: Option<i64>,
+    pub components: Vec<Component>,
+}
+
+/// Which of the ranked candidates a search fetches
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CandidateRange {
+    /// The best candidates, without counting the matching parts
+    Best(i64),
+    /// A page of candidates along with the number of matching parts
+    Page { limit: i64, offset: i64 },
+}
+
+/// Pagination of a ranked candidate list
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct Pagination {
+    /// Maximum number of candidates to return, defaults to 10 and is capped at 100
+    pub limit: Option<i64>,
+    /// Number of candidates to skip, defaults to 0
+    pub offset: Option<i64>,
+}
+
+impl Pagination {
+    pub const DEFAULT_LIMIT: i64 = 10;
+    pub const MAX_LIMIT: i64 = 100;
+
+    /// The page of candidates to fetch
+    pub fn range(&self) -> CandidateRange {
+        CandidateRange::Page {
+            limit: self
+                .limit
+                .unwrap_or(Self::DEFAULT_LIMIT)
+                .clamp(0, Self::MAX_LIMIT),
+            offset: self.offset.unwrap_or(0).max(0),
+        }
+    }
+
+    /// The page of a search fetched with `range`
+    pub fn paginate(&self, results: SearchResults) -> ComponentPage {
+        let CandidateRange::Page { limit, offset } = self.range() else {
+            unreachable!("pagination always fetches a page")
+        };
+        ComponentPage {
+            total: results.total.unwrap_or_default(),
+            limit,
+            offset,
+            components: results.components,
+        }
+    }
+}
+
+/// A page of ranked candidates, in the same order the single part request picks from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ComponentPage {
+    /// Total number of matching parts
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub components: Vec<Component>,
 }
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::jlc::mpn::MpnMatch;
+use crate::jlc::ranking::RankingOptions;
+
+/// Search by manufacturer part number
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct MpnSearchRequest {
+    /// Manufacturer part number, e.g. `RC0402FR-0710KL`
+    pub mpn: String,
+    /// How the part number is matched, defaults to exact
+    #[serde(rename = "match")]
+    pub match_mode: Option<MpnMatch>,
+    pub package: Option<String>,
+    /// Only return basic parts when true, only extended parts when false
+    pub basic: Option<bool>,
+    /// Closer part numbers come first, the ranking orders parts that match equally well
+    #[serde(flatten)]
+    pub ranking: RankingOptions,
+}
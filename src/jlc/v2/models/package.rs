@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// A canonical package name with the part counts of all its spellings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Package {
+    pub package: String,
+    pub part_count: i64,
+    pub basic_count: i64,
+    pub in_stock_count: i64,
+}
+
+/// Category filter of the package listing, every filter is optional
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct PackageQuery {
+    pub category: Option<String>,
+    pub subcategory: Option<String>,
+    pub category_id: Option<i64>,
+}
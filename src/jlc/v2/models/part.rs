@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::jlc::price::PriceTier;
+
+/// The full stored record of a part
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PartRecord {
+    pub lcsc: String,
+    pub category_id: i64,
+    pub category: Option<String>,
+    pub subcategory: Option<String>,
+    pub mfr: Option<String>,
+    pub package: Option<String>,
+    pub joints: i64,
+    pub manufacturer: String,
+    pub basic: bool,
+    pub description: Option<String>,
+    pub datasheet: Option<String>,
+    pub stock: i64,
+    pub price: Option<f64>,
+    /// Price breaks, lowest quantity first
+    #[schema(value_type = Option<Vec<PriceTier>>)]
+    pub price_tiers: Option<Json<Vec<PriceTier>>>,
+    /// Time of the last scrape of this part, ISO 8601 in UTC
+    pub last_update: Option<String>,
+    pub resistance: Option<f64>,
+    pub inductance: Option<f64>,
+    pub capacitance: Option<f64>,
+    pub dielectric: Option<String>,
+    pub current: Option<f64>,
+    pub voltage: Option<f64>,
+    /// Power rating in watt, parsed from the description
+    pub power: Option<f64>,
+    /// Manufacturer tolerance in percent, parsed from the description
+    pub tolerance: Option<f64>,
+    /// Saturation current of inductors in ampere, parsed from the description
+    pub saturation_current: Option<f64>,
+    /// DC resistance of inductors in ohm, parsed from the description
+    pub dcr: Option<f64>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct PartBatchRequest {
+    /// LCSC part numbers, e.g. "C25804"
+    pub lcsc: Vec<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PartBatchResponse {
+    /// Found parts, in request order
+    pub parts: Vec<PartRecord>,
+    /// Requested LCSC part numbers that are not stored
+    pub missing: Vec<String>,
+}
+
+/// Normalize an LCSC part number, "c25804" and "25804" both become "C25804"
+pub fn normalize_lcsc(lcsc: &str) -> String {
+    let lcsc = lcsc.trim().to_ascii_uppercase();
+    if lcsc.chars().all(|c| c.is_ascii_digit()) {
+        format!("C{}", lcsc)
+    } else {
+        lcsc
+    }
+}
@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
+use crate::jlc::e_series::ESeries;
+use crate::jlc::quantity::{prefix_multiplier, request_quantity, Quantity, Unit, ValueFields};
+use crate::jlc::ranking::RankingOptions;
+use crate::jlc::stock::OrderOptions;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 pub enum ResistorUnit {
     MegaOhm,
@@ -12,12 +17,80 @@ pub enum ResistorUnit {
     PicoOhm,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+impl ResistorUnit {
+    /// SI prefix of the unit
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            ResistorUnit::MegaOhm => "M",
+            ResistorUnit::KiloOhm => "k",
+            ResistorUnit::Ohm => "",
+            ResistorUnit::MilliOhm => "m",
+            ResistorUnit::MicroOhm => "u",
+            ResistorUnit::NanoOhm => "n",
+            ResistorUnit::PicoOhm => "p",
+        }
+    }
+
+    /// Multiplier from the unit to ohm
+    pub fn multiplier(&self) -> f64 {
+        prefix_multiplier(self.prefix()).unwrap_or(1.0)
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
 pub struct ResistorRequest {
-    pub unit: ResistorUnit,
-    pub value: f64,
+    pub unit: Option<ResistorUnit>,
+    pub value: Option<f64>,
+    /// Value with unit and optional inline tolerance as a single string, e.g. "4k7 ±1%",
+    /// replaces `value`, `unit` and, when a tolerance is given, the tolerance fields
+    pub value_string: Option<String>,
+    /// E-series the nominal value is snapped to before searching, the tolerance is applied
+    /// around the snapped value, e.g. E24 searches 4.87k as 4.7k
+    pub e_series: Option<ESeries>,
     pub package: Option<String>,
+    /// Allowed packages, combined with `package`
+    pub packages: Option<Vec<String>>,
+    /// Largest allowed chip package as an imperial size code, e.g. "0603"
+    pub max_package_size: Option<String>,
     pub tolerance_percentage: Option<f64>,
     pub absolute_tolerance: Option<f64>,
     pub absolute_tolerance_unit: Option<ResistorUnit>,
+    /// Minimum power rating in watt, e.g. 0.25 for a 1/4 W part
+    pub min_power: Option<f64>,
+    /// Largest manufacturer tolerance in percent, e.g. 1.0 for 1% or better parts
+    pub max_part_tolerance: Option<f64>,
+    #[serde(flatten)]
+    pub order: OrderOptions,
+    /// Only return basic parts and preferred extended parts
+    pub basic_only: Option<bool>,
+    /// LCSC part numbers of extended parts that are ranked and filtered as basic parts,
+    /// in addition to the preferred parts configured on the server
+    pub preferred_parts: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub ranking: RankingOptions,
+}
+
+impl ResistorRequest {
+    /// The requested value in ohm with its tolerance, from `value_string`
+    /// or from the separate value, unit and tolerance fields, snapped to `e_series` when set
+    pub fn quantity(&self) -> Result<Quantity, String> {
+        let mut quantity = request_quantity(
+            ValueFields {
+                value_string: self.value_string.as_deref(),
+                value: self.value,
+                multiplier: self.unit.as_ref().map(ResistorUnit::multiplier),
+                tolerance_percentage: self.tolerance_percentage,
+                absolute_tolerance: self.absolute_tolerance,
+                absolute_tolerance_multiplier: self
+                    .absolute_tolerance_unit
+                    .as_ref()
+                    .map(ResistorUnit::multiplier),
+            },
+            Unit::Ohm,
+        )?;
+        if let Some(series) = self.e_series {
+            quantity.value = series.snap(quantity.value);
+        }
+        Ok(quantity)
+    }
 }
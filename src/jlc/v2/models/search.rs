@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::jlc::ranking::RankingOptions;
+use crate::jlc::stock::OrderOptions;
+
+/// Parametric search over any JLC category, every filter is optional
+/// but at least a category, subcategory or category id must be given
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct PartSearchRequest {
+    /// Category name, e.g. `Diodes`
+    pub category: Option<String>,
+    /// Subcategory name, e.g. `Schottky Barrier Diodes (SBD)`
+    pub subcategory: Option<String>,
+    /// Categories table id as listed by `/jlc/v2/categories/subcategories`
+    pub category_id: Option<i64>,
+    pub package: Option<String>,
+    /// Only return basic parts when true, only extended parts when false
+    pub basic: Option<bool>,
+    /// Maximum unit price in USD, at the price tier of the ordered quantity
+    pub max_price: Option<f64>,
+    /// Manufacturer name, case insensitive
+    pub manufacturer: Option<String>,
+    /// Minimum rated voltage in volt
+    pub min_voltage: Option<f64>,
+    /// Maximum rated voltage in volt
+    pub max_voltage: Option<f64>,
+    /// Minimum rated current in ampere
+    pub min_current: Option<f64>,
+    /// Maximum rated current in ampere
+    pub max_current: Option<f64>,
+    #[serde(flatten)]
+    pub order: OrderOptions,
+    #[serde(flatten)]
+    pub ranking: RankingOptions,
+}
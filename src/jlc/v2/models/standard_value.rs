@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::jlc::e_series::ESeries;
+
+/// Range, series and package of the standard value listing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct StandardValueQuery {
+    /// Part type, `resistor`, `capacitor` or `inductor`
+    pub part_type: String,
+    pub series: ESeries,
+    /// Smallest value of the range with an optional unit, e.g. "1k" or "10nF"
+    pub min_value: String,
+    /// Largest value of the range with an optional unit, e.g. "100k" or "1uF"
+    pub max_value: String,
+    pub package: String,
+    /// Minimum stock of a part, defaults to 1 so only stocked parts are counted
+    pub min_stock: Option<i64>,
+}
+
+/// A standard value with the parts stocked at that value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct StandardValue {
+    /// Value in the unit of the value column, ohm for resistors, pF for capacitors
+    /// and pH for inductors
+    pub value: f64,
+    pub part_count: i64,
+    pub basic_count: i64,
+    /// Total stock of the parts
+    pub stock: i64,
+}
+
+/// The standard values of a series in a range that are stocked in a package, in ascending order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct StandardValues {
+    pub series: ESeries,
+    pub package: String,
+    pub values: Vec<StandardValue>,
+}
@@ -0,0 +1,401 @@
+use std::collections::{HashMap, HashSet};
+
+use sqlx::PgPool;
+
+use self::bom::{BomItemRequest, BomItemResult, BomPart, BomPartRequest, BomRequest, BomResponse};
+use crate::jlc::error::PartError;
+use crate::jlc::ranking::Ranking;
+use crate::jlc::v1;
+use crate::jlc::v1::jlc_models::{JLCPartRequest, JLCValue};
+use crate::jlc::v1::jlc_part_finder::{component_vec_to_jlcpb_part_response, find_candidates};
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::capacitor::find_capacitor;
+use crate::jlc::v2::services::inductor::find_inductor;
+use crate::jlc::v2::services::resistor::find_resistor;
+use crate::AppState;
+
+/// Number of candidates of every item a consolidated BOM chooses a shared part from
+const CONSOLIDATION_CANDIDATES: i64 = 100;
+
+/// The ranked parts matching a BOM item, best first
+pub enum Candidates {
+    V1(
+        Box<JLCPartRequest>,
+        Vec<v1::jlc_part_finder::Component>,
+        JLCValue,
+    ),
+    V2(Vec<Component>),
+}
+
+/// The candidates of a BOM item with the ranking that ordered them, the ranking
+/// tells which extended parts are preferred and count as basic parts
+pub struct ItemCandidates {
+    candidates: Candidates,
+    ranking: Ranking,
+}
+
+impl ItemCandidates {
+    /// LCSC part number and basic or preferred flag of every candidate, best first
+    fn parts(&self) -> Vec<(&str, bool)> {
+        match &self.candidates {
+            Candidates::V1(_, components, _) => components
+                .iter()
+                .map(|component| (component.lcsc.as_str(), self.ranking.is_basic(component)))
+                .collect(),
+            Candidates::V2(components) => components
+                .iter()
+                .map(|component| (component.lcsc.as_str(), self.ranking.is_basic(component)))
+                .collect(),
+        }
+    }
+
+    /// The candidate with the given LCSC part number, the best candidate when `None`,
+    /// and whether it counts as a basic part
+    fn into_part(self, lcsc: Option<&str>) -> (BomPart, bool) {
+        match self.candidates {
+            Candidates::V1(request, mut components, jlc_value) => {
+                if let Some(lcsc) = lcsc {
+                    components.retain(|component| component.lcsc == lcsc);
+                }
+                let basic = self.ranking.is_basic(components.first().unwrap());
+                let response =
+                    component_vec_to_jlcpb_part_response(*request, components, jlc_value);
+                (BomPart::V1(response.best_component), basic)
+            }
+            Candidates::V2(components) => {
+                let index = lcsc
+                    .and_then(|lcsc| {
+                        components
+                            .iter()
+                            .position(|component| component.lcsc == lcsc)
+                    })
+                    .unwrap_or(0);
+                let component = components.into_iter().nth(index).unwrap();
+                let basic = self.ranking.is_basic(&component);
+                (BomPart::V2(component), basic)
+            }
+        }
+    }
+}
+
+/// Resolve every BOM item concurrently, the shared pool bounds the number of
+/// queries in flight. The results keep the request order.
+pub async fn resolve_bom(pool: PgPool, state: AppState, request: BomRequest) -> BomResponse {
+    tracing::info!(
+        "Resolving BOM with {} items, consolidate: {:?}",
+        request.items.len(),
+        request.consolidate
+    );
+    // only a consolidated BOM looks past the best candidate of an item
+    let range = if request.consolidate.unwrap_or(false) {
+        CandidateRange::Best(CONSOLIDATION_CANDIDATES)
+    } else {
+        CandidateRange::Best(1)
+    };
+    let handles: Vec<_> = request
+        .items
+        .into_iter()
+        .map(|item| {
+            let designator = item.designator.clone();
+            (
+                designator,
+                tokio::spawn(find_item_candidates(
+                    pool.clone(),
+                    state.clone(),
+                    item,
+                    range,
+                )),
+            )
+        })
+        .collect();
+
+    let mut designators = Vec::with_capacity(handles.len());
+    let mut candidates = Vec::with_capacity(handles.len());
+    for (designator, handle) in handles {
+        let item_candidates = match handle.await {
+            Ok(item_candidates) => item_candidates,
+            Err(error) => Err(PartError::Internal(error.to_string())),
+        };
+        designators.push(designator);
+        candidates.push(item_candidates);
+    }
+
+    // pick the best candidate of every item unless the BOM is consolidated
+    let chosen = if request.consolidate.unwrap_or(false) {
+        let parts: Vec<Vec<(&str, bool)>> = candidates
+            .iter()
+            .map(|item_candidates| match item_candidates {
+                Ok(item_candidates) => item_candidates.parts(),
+                Err(_) => Vec::new(),
+            })
+            .collect();
+        consolidate(&parts)
+    } else {
+        vec![None; candidates.len()]
+    };
+
+    // preferred parts count as basic parts, like in the ranking
+    let mut basic_parts = Vec::with_capacity(candidates.len());
+    let items: Vec<BomItemResult> = designators
+        .into_iter()
+        .zip(candidates)
+        .zip(chosen)
+        .map(
+            |((designator, item_candidates), lcsc)| match item_candidates {
+                Ok(item_candidates) => {
+                    let (part, basic) = item_candidates.into_part(lcsc.as_deref());
+                    basic_parts.push(basic);
+                    BomItemResult {
+                        designator,
+                        part: Some(part),
+                        error: None,
+                    }
+                }
+                Err(error) => BomItemResult {
+                    designator,
+                    part: None,
+                    error: Some(error.body()),
+                },
+            },
+        )
+        .collect();
+
+    let parts: Vec<&BomPart> = items.iter().filter_map(|item| item.part.as_ref()).collect();
+    let unique_parts: HashSet<&str> = parts.iter().map(|part| part.lcsc()).collect();
+    let extended_parts: HashSet<&str> = parts
+        .iter()
+        .zip(&basic_parts)
+        .filter(|(_, basic)| !**basic)
+        .map(|(part, _)| part.lcsc())
+        .collect();
+    BomResponse {
+        unique_parts: unique_parts.len() as i64,
+        extended_parts: extended_parts.len() as i64,
+        items,
+    }
+}
+
+/// Pick one LCSC part per item so the BOM uses few unique and few extended parts.
+/// Parts are chosen greedily, basic parts first and then the part matching the most
+/// items that are still open, ties go to the part ranked best over those items.
+/// Items without candidates are left as `None`.
+pub fn consolidate(candidates: &[Vec<(&str, bool)>]) -> Vec<Option<String>> {
+    let mut chosen: Vec<Option<String>> = vec![None; candidates.len()];
+    loop {
+        // basic flag, number of open items and summed rank over those items per part
+        let mut scores: HashMap<&str, (bool, usize, usize)> = HashMap::new();
+        for (item, item_candidates) in candidates.iter().enumerate() {
+            if chosen[item].is_some() {
+                continue;
+            }
+            for (rank, (lcsc, basic)) in item_candidates.iter().enumerate() {
+                let score = scores.entry(lcsc).or_insert((*basic, 0, 0));
+                score.1 += 1;
+                score.2 += rank;
+            }
+        }
+
+        let best = scores.into_iter().max_by(|(lcsc_a, a), (lcsc_b, b)| {
+            a.0.cmp(&b.0)
+                .then(a.1.cmp(&b.1))
+                .then(b.2.cmp(&a.2))
+                .then(lcsc_b.cmp(lcsc_a))
+        });
+        let Some((best_lcsc, _)) = best else {
+            return chosen;
+        };
+        for (item, item_candidates) in candidates.iter().enumerate() {
+            if chosen[item].is_none() && item_candidates.iter().any(|(lcsc, _)| *lcsc == best_lcsc)
+            {
+                chosen[item] = Some(best_lcsc.to_string());
+            }
+        }
+    }
+}
+
+async fn find_item_candidates(
+    pool: PgPool,
+    state: AppState,
+    item: BomItemRequest,
+    range: CandidateRange,
+) -> Result<ItemCandidates, PartError> {
+    match item.part {
+        BomPartRequest::V1(request) => {
+            let ranking = preferred_ranking(&state, &None);
+            let (components, jlc_value) =
+                find_candidates(pool, state, request.clone(), range).await?;
+            Ok(ItemCandidates {
+                candidates: Candidates::V1(Box::new(request), components, jlc_value),
+                ranking,
+            })
+        }
+        BomPartRequest::Resistor(request) => {
+            let ranking = preferred_ranking(&state, &request.preferred_parts);
+            v2_candidates(find_resistor(pool, state, request, range).await, ranking)
+        }
+        BomPartRequest::Capacitor(request) => {
+            let ranking = preferred_ranking(&state, &request.preferred_parts);
+            v2_candidates(find_capacitor(pool, state, request, range).await, ranking)
+        }
+        BomPartRequest::Inductor(request) => {
+            let ranking = preferred_ranking(&state, &request.preferred_parts);
+            v2_candidates(find_inductor(pool, state, request, range).await, ranking)
+        }
+    }
+}
+
+/// The ranking of an item's preferred parts, the server wide ones and those of the request
+fn preferred_ranking(state: &AppState, requested: &Option<Vec<String>>) -> Ranking {
+    Ranking::new(None, None, None).with_preferred_parts(state.preferred_parts(requested))
+}
+
+/// The ranked candidates of a v2 search, the searches fail when there are none
+fn v2_candidates(
+    result: Result<SearchResults, PartError>,
+    ranking: Ranking,
+) -> Result<ItemCandidates, PartError> {
+    result.map(|results| ItemCandidates {
+        candidates: Candidates::V2(results.components),
+        ranking,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jlc::package::package_aliases;
+    use crate::jlc::v1::jlc_models::{JLCPartRequest, JLCValue};
+    use crate::jlc::v2::models::resistor::{ResistorRequest, ResistorUnit};
+    use crate::jlc::v2::services::part::find_parts_by_lcsc;
+    use clap::Parser;
+    use dotenv::dotenv;
+
+    use crate::Args;
+
+    fn resistor_request(value: f64) -> ResistorRequest {
+        ResistorRequest {
+            unit: Some(ResistorUnit::KiloOhm),
+            value: Some(value),
+            package: Some("0402".to_string()),
+            tolerance_percentage: Some(1.0),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_bom() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+        let request = BomRequest {
+            items: vec![
+                BomItemRequest {
+                    designator: "R1".to_string(),
+                    part: BomPartRequest::Resistor(resistor_request(10.0)),
+                },
+                BomItemRequest {
+                    designator: "R2".to_string(),
+                    part: BomPartRequest::Resistor(ResistorRequest {
+                        min_power: Some(-1.0),
+                        ..resistor_request(10.0)
+                    }),
+                },
+                BomItemRequest {
+                    designator: "R3".to_string(),
+                    part: BomPartRequest::V1(JLCPartRequest {
+                        type_field: "resistor".to_string(),
+                        designator_prefix: "R".to_string(),
+                        mpn: "generic_resistor".to_string(),
+                        value: JLCValue {
+                            unit: "kiloohm".to_string(),
+                            min_val: 9.9,
+                            max_val: 10.1,
+                            nominal: 10.0,
+                        },
+                        package: Some("0402".to_string()),
+                        ..Default::default()
+                    }),
+                },
+            ],
+            consolidate: None,
+        };
+        let response = resolve_bom(pool.clone(), AppState::default(), request).await;
+
+        // the results keep the request order and an invalid item fails on its own
+        let designators: Vec<&str> = response
+            .items
+            .iter()
+            .map(|item| item.designator.as_str())
+            .collect();
+        assert_eq!(designators, vec!["R1", "R2", "R3"]);
+        let error = response.items[1].error.as_ref().unwrap();
+        assert_eq!(error.error, "invalid_request");
+        assert!(response.items[1].part.is_none());
+
+        // every resolved part is a 10k ±1% resistor in the requested package
+        let lcsc: Vec<String> = [&response.items[0], &response.items[2]]
+            .iter()
+            .map(|item| item.part.as_ref().unwrap().lcsc().to_string())
+            .collect();
+        let stored_parts = find_parts_by_lcsc(pool, lcsc.clone()).await.unwrap().parts;
+        assert!(lcsc
+            .iter()
+            .all(|lcsc| stored_parts.iter().any(|part| part.lcsc == *lcsc)));
+        let packages = package_aliases("0402");
+        assert!(stored_parts.iter().all(|part| {
+            part.resistance
+                .is_some_and(|resistance| (9_900.0..=10_100.0).contains(&resistance))
+                && packages.contains(part.package.as_ref().unwrap())
+        }));
+        let unique_parts: HashSet<&String> = lcsc.iter().collect();
+        assert_eq!(response.unique_parts, unique_parts.len() as i64);
+    }
+
+    #[tokio::test]
+    async fn test_preferred_parts_are_basic() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+        let bom = |request: ResistorRequest| BomRequest {
+            items: vec![BomItemRequest {
+                designator: "R1".to_string(),
+                part: BomPartRequest::Resistor(request),
+            }],
+            consolidate: Some(true),
+        };
+
+        // preferring the chosen part makes it count as a basic part
+        let response = resolve_bom(
+            pool.clone(),
+            AppState::default(),
+            bom(resistor_request(9.53)),
+        )
+        .await;
+        let lcsc = response.items[0].part.as_ref().unwrap().lcsc().to_string();
+        let mut request = resistor_request(9.53);
+        request.preferred_parts = Some(vec![lcsc.clone()]);
+        let response = resolve_bom(pool, AppState::default(), bom(request)).await;
+        assert_eq!(response.items[0].part.as_ref().unwrap().lcsc(), lcsc);
+        assert_eq!(response.extended_parts, 0);
+    }
+
+    #[test]
+    fn test_consolidate() {
+        // the extended C2 is shared by the first two items, the third one has a basic part
+        let candidates = vec![
+            vec![("C1", false), ("C2", false)],
+            vec![("C2", false), ("C3", false)],
+            vec![("C4", false), ("C5", true)],
+            vec![],
+        ];
+        assert_eq!(
+            consolidate(&candidates),
+            vec![
+                Some("C2".to_string()),
+                Some("C2".to_string()),
+                Some("C5".to_string()),
+                None
+            ]
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use sqlx::postgres::PgArguments;
+use sqlx::{Arguments, Encode, FromRow, PgPool, Postgres, Row, Type};
+
+use crate::jlc::constraints::{SearchConstraints, ValueColumn};
+use crate::jlc::error::PartError;
+use crate::jlc::ranking::{Ranking, RankingOptions, RankingStrategy};
+use crate::jlc::v2::models::*;
+
+/// The stored attributes of a candidate, over the parts table `p`
+const PART_COLUMNS: &str = "p.lcsc, p.category_id::int8 as category_id, p.mfr, p.package, p.joints::int8 as joints, p.manufacturer, p.basic, p.description, p.datasheet, p.stock::int8 as stock, p.price, p.dielectric, p.voltage, p.current, p.resistance, p.capacitance, p.inductance, p.power, p.tolerance, p.saturation_current, p.dcr";
+
+/// Basic and preferred parts first, then closest to the nominal value, then highest stock,
+/// then by LCSC number, the order every strategy breaks ties with
+const PREFER_BASIC: &str = "preferred DESC, relative_error ASC NULLS LAST, stock DESC, lcsc";
+
+/// A search over the parts table that ranks every matching part in SQL and fetches only
+/// the requested range of them, so no strategy only sees a truncated candidate list
+#[derive(Default)]
+pub struct CandidateQuery {
+    /// FROM clause of the matching parts, the parts table has to be named `p`
+    pub source: String,
+    /// The parameters of `source`
+    pub arguments: PgArguments,
+    /// Column of the value the relative error to the nominal value of the ranking is computed on
+    pub value: Option<ValueColumn>,
+    /// Expression ordering the candidates before the ranking strategy, lower is better
+    pub relevance: Option<String>,
+    /// Number of parts ordered, picks the price tier and the unit price
+    pub order_quantity: Option<i64>,
+}
+
+impl CandidateQuery {
+    /// The parts of a value search meeting every constraint
+    pub fn constrained(
+        constraints: &SearchConstraints,
+        order_quantity: Option<i64>,
+    ) -> Result<Self, sqlx::Error> {
+        Ok(CandidateQuery {
+            source: constraints.source(),
+            arguments: constraints.arguments()?,
+            value: Some(constraints.column),
+            order_quantity,
+            ..Default::default()
+        })
+    }
+
+    /// Add a parameter and return its placeholder
+    pub fn bind<'q, T>(&mut self, value: T) -> Result<String, sqlx::Error>
+    where
+        T: 'q + Encode<'q, Postgres> + Type<Postgres>,
+    {
+        self.arguments.add(value).map_err(sqlx::Error::Encode)?;
+        Ok(format!("${}", self.arguments.len()))
+    }
+
+    /// Rank the matching parts and fetch the requested range of them, a page along with
+    /// the number of matching parts
+    pub async fn fetch(
+        mut self,
+        pool: &PgPool,
+        ranking: &Ranking,
+        range: CandidateRange,
+    ) -> Result<SearchResults, sqlx::Error> {
+        let quantity = self.bind(self.order_quantity)?;
+        let nominal = self.bind(ranking.nominal)?;
+        let preferred = self.bind(ranking.preferred_parts.clone())?;
+        let relative_error = match self.value {
+            Some(column) => format!(
+                "CASE WHEN {nominal} = 0 THEN abs(p.{column}) ELSE abs((p.{column} - {nominal}) / {nominal}) END",
+                column = column.name()
+            ),
+            None => "NULL::float8".to_string(),
+        };
+        let relevance = self
+            .relevance
+            .clone()
+            .unwrap_or_else(|| "NULL::float8".to_string());
+        let (score, order) = self.order(ranking)?;
+        let order = format!("relevance ASC NULLS LAST, {order}");
+
+        // the window functions of the score see every matching part before the range is taken
+        let ranked = format!(
+            "SELECT *, {score} as score FROM (SELECT {PART_COLUMNS}, price_tier(p.price_tiers, {quantity}) as price_tier, {relative_error} as relative_error, NULL::text as e_series, NULL::float8 as e_series_value, (p.basic OR p.lcsc = ANY({preferred})) as preferred, unit_price(p.price_tiers, p.price, {quantity}) as unit_price, {relevance} as relevance {}) candidates",
+            self.source
+        );
+        let mut results = SearchResults::default();
+        match range {
+            CandidateRange::Best(count) => {
+                let count = self.bind(count)?;
+                let query =
+                    format!("SELECT * FROM ({ranked}) ranked ORDER BY {order} LIMIT {count}");
+                let rows = sqlx::query_with(&query, self.arguments)
+                    .fetch_all(pool)
+                    .await?;
+                for row in rows {
+                    results.components.push(Component::from_row(&row)?);
+                }
+            }
+            CandidateRange::Page { limit, offset } => {
+                // the count is joined to the page so a page past the last part still has a total
+                let first = self.bind(offset)?;
+                let last = self.bind(offset.saturating_add(limit))?;
+                let query = format!(
+                    "SELECT page.*, matches.total FROM (SELECT count(*) as total {}) matches LEFT JOIN (SELECT *, row_number() OVER (ORDER BY {order}) as position FROM ({ranked}) ranked) page ON page.position > {first} and page.position <= {last} ORDER BY page.position",
+                    self.source
+                );
+                let rows = sqlx::query_with(&query, self.arguments)
+                    .fetch_all(pool)
+                    .await?;
+                for row in rows {
+                    results.total = Some(row.try_get("total")?);
+                    if row.try_get::<Option<i64>, _>("position")?.is_some() {
+                        results.components.push(Component::from_row(&row)?);
+                    }
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Score and ORDER BY clause of the ranking strategy
+    fn order(&mut self, ranking: &Ranking) -> Result<(String, String), sqlx::Error> {
+        let order = match ranking.strategy {
+            RankingStrategy::PreferBasic => PREFER_BASIC.to_string(),
+            RankingStrategy::Cheapest => format!("unit_price ASC NULLS LAST, {PREFER_BASIC}"),
+            RankingStrategy::ClosestToNominal => {
+                format!("relative_error ASC NULLS LAST, {PREFER_BASIC}")
+            }
+            RankingStrategy::HighestStock => format!("stock DESC, {PREFER_BASIC}"),
+            RankingStrategy::Weighted => {
+                let score = self.weighted_score(ranking)?;
+                return Ok((score, format!("score DESC, {PREFER_BASIC}")));
+            }
+        };
+        Ok(("NULL::float8".to_string(), order))
+    }
+
+    /// Weighted score of a candidate, normalized over every matching part, see `RankingWeights`
+    fn weighted_score(&mut self, ranking: &Ranking) -> Result<String, sqlx::Error> {
+        let weights = &ranking.weights;
+        let scores = [
+            (weights.basic, "preferred::int"),
+            (weights.price, "CASE WHEN unit_price > 0 THEN min(unit_price) FILTER (WHERE unit_price > 0) OVER () / unit_price WHEN unit_price IS NOT NULL THEN 1 ELSE 0 END"),
+            (weights.nominal, "CASE WHEN relative_error IS NULL THEN 0 WHEN max(relative_error) OVER () = 0 THEN 1 ELSE 1 - relative_error / max(relative_error) OVER () END"),
+            (weights.stock, "CASE WHEN max(greatest(stock, 0)) OVER () > 0 THEN ln(1 + greatest(stock, 0)::float8) / ln(1 + max(greatest(stock, 0)) OVER ()::float8) ELSE 0 END"),
+            (weights.joints, "CASE WHEN joints > 0 THEN min(joints) FILTER (WHERE joints > 0) OVER ()::float8 / joints ELSE 0 END"),
+        ];
+        let mut terms = Vec::new();
+        for (weight, score) in scores {
+            terms.push(format!("{} * {score}", self.bind(weight)?));
+        }
+        Ok(terms.join(" + "))
+    }
+}
+
+/// The best candidates of a value search by the requested ranking, v1 and v2 alike, an error
+/// naming the constraint that eliminated the last candidates when nothing matches
+pub async fn constrained_candidates(
+    pool: &PgPool,
+    constraints: &SearchConstraints,
+    ranking: &RankingOptions,
+    order_quantity: Option<i64>,
+    range: CandidateRange,
+) -> Result<SearchResults, PartError> {
+    let ranking = Ranking::new(
+        ranking.strategy,
+        ranking.weights.clone(),
+        Some(constraints.nominal),
+    )
+    .with_preferred_parts(constraints.preferred_parts.clone());
+    let results = CandidateQuery::constrained(constraints, order_quantity)?
+        .fetch(pool, &ranking, range)
+        .await?;
+    // a page past the last match is empty but not a failed search
+    if results.components.is_empty() && results.total.unwrap_or_default() == 0 {
+        return Err(constraints.no_match(pool).await);
+    }
+    Ok(results)
+}
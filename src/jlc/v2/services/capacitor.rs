@@ -1,90 +1,179 @@
 use sqlx::PgPool;
 
-use self::capacitor::{CapacitorRequest, CapacitorUnit};
+use self::capacitor::{dielectric_names, CapacitorRequest};
+use crate::jlc::constraints::{SearchConstraints, SearchDiagnostics, ValueColumn};
+use crate::jlc::error::PartError;
+use crate::jlc::package::{allowed_package_aliases, chip_size};
+use crate::jlc::quantity::Unit;
 use crate::jlc::v2::models::*;
-
-pub enum Tolerance {
-    Up,
-    Down,
-}
+use crate::jlc::v2::services::candidates::constrained_candidates;
+use crate::AppState;
 
 pub async fn find_capacitor(
     pool: PgPool,
+    state: AppState,
     request: CapacitorRequest,
-) -> Result<Vec<Component>, sqlx::Error> {
-    // get the nominal value and tolerance values
-    let jlc_farad_value = get_capacitor_value(request.value, request.unit.clone());
-    let jlc_farad_tolerance_up = get_capacitor_tolerance(request.clone(), Tolerance::Up);
-    let jlc_farad_tolerance_down = get_capacitor_tolerance(request.clone(), Tolerance::Down);
+    range: CandidateRange,
+) -> Result<SearchResults, PartError> {
+    let constraints = search_constraints(&pool, &state, &request).await?;
     tracing::info!(
-        "Searching for capacitor with value: {} farad, min: {} farad, max: {} farad",
-        jlc_farad_value * 1e-12,
-        jlc_farad_tolerance_down * 1e-12,
-        jlc_farad_tolerance_up * 1e-12
+        "Searching for capacitor with value: {} farad, min: {} farad, max: {} farad, voltage: {:?}-{:?} volt",
+        Unit::Farad.from_stored(constraints.nominal),
+        Unit::Farad.from_stored(constraints.min_value),
+        Unit::Farad.from_stored(constraints.max_value),
+        request.min_voltage,
+        request.max_voltage
     );
+    let order_quantity = request.order.order_quantity()?;
 
-    let capacitor_category_id: (i32,) = sqlx::query_as("SELECT id FROM categories WHERE name = 'Capacitors' and subcategory_name = 'Multilayer Ceramic Capacitors MLCC - SMD/SMT'")
-    .fetch_one(&pool).await?;
+    // every matching part is ranked before the best candidates are fetched, the constraints
+    // are the ones the diagnostics count and name the eliminating one when nothing matches
+    let mut results =
+        constrained_candidates(&pool, &constraints, &request.ranking, order_quantity, range)
+            .await?;
 
-    // if request.package is not None, filter components_df on package = request.package
-    if request.package.is_some() {
-        let matching_parts: Vec<Component> = sqlx::query_as!(
-            Component,
-            r#"SELECT lcsc as "lcsc!", category_id as "category_id!", mfr as "mfr?", package as "package?", joints as "joints!", manufacturer as "manufacturer!", basic as "basic!", description as "description?", datasheet as "datasheet?", stock as "stock!", price as "price?", dielectric as "dielectric?" FROM parts WHERE category_id = $1 and capacitance between $2 and $3 and package = $4 ORDER BY basic DESC LIMIT 100"#,
-            capacitor_category_id.0,
-            jlc_farad_tolerance_down,
-            jlc_farad_tolerance_up,
-            request.package.unwrap()
-        ).fetch_all(&pool).await?;
-        return Ok(matching_parts);
-    } else {
-        let matching_parts: Vec<Component> = sqlx::query_as!(
-            Component,
-            r#"SELECT lcsc as "lcsc!", category_id as "category_id!", mfr as "mfr?", package as "package?", joints as "joints!", manufacturer as "manufacturer!", basic as "basic!", description as "description?", datasheet as "datasheet?", stock as "stock!", price as "price?", dielectric as "dielectric?" FROM parts WHERE category_id = $1 and capacitance between $2 and $3 ORDER BY basic DESC LIMIT 100"#,
-            capacitor_category_id.0,
-            jlc_farad_tolerance_down,
-            jlc_farad_tolerance_up
-        ).fetch_all(&pool).await?;
-        return Ok(matching_parts);
+    // report the E-series value the search used instead of the requested value
+    if let Some(series) = request.e_series {
+        for component in results.components.iter_mut() {
+            component.e_series = Some(series.name().to_string());
+            component.e_series_value = Some(constraints.nominal);
+        }
     }
+    Ok(results)
 }
 
-pub fn get_capacitor_tolerance(request: CapacitorRequest, tolerance: Tolerance) -> f64 {
-    let nominal_value = get_capacitor_value(request.value, request.unit);
-    // check if absolute_tolerance is set or tolerance_percentage is set
-    if request.absolute_tolerance.is_some() {
-        let absolute_tolerance = request.absolute_tolerance.unwrap();
-        let absolute_tolerance_unit = request.absolute_tolerance_unit.unwrap();
-        let tolerance_value = get_capacitor_value(absolute_tolerance, absolute_tolerance_unit);
-        match tolerance {
-            Tolerance::Up => {
-                return nominal_value + tolerance_value;
-            }
-            Tolerance::Down => {
-                return nominal_value - tolerance_value;
-            }
-        }
-    } else {
-        let tolerance_pct = request.tolerance_percentage.unwrap();
-        match tolerance {
-            Tolerance::Up => {
-                return nominal_value + (nominal_value * (tolerance_pct / 100.0));
-            }
-            Tolerance::Down => {
-                return nominal_value - (nominal_value * (tolerance_pct / 100.0));
-            }
+/// Count the capacitors left after each filter of the request and suggest
+/// the nearest relaxations when none is left
+pub async fn diagnose_capacitor(
+    pool: PgPool,
+    state: AppState,
+    request: CapacitorRequest,
+) -> Result<SearchDiagnostics, PartError> {
+    let constraints = search_constraints(&pool, &state, &request).await?;
+    Ok(constraints.diagnose(&pool).await?)
+}
+
+/// The filters of a capacitor request, values in pF
+async fn search_constraints(
+    pool: &PgPool,
+    state: &AppState,
+    request: &CapacitorRequest,
+) -> Result<SearchConstraints, PartError> {
+    validate_request(request).map_err(PartError::InvalidRequest)?;
+    let quantity = request.quantity().map_err(PartError::InvalidRequest)?;
+    let (min_value, max_value) = quantity.bounds();
+    let capacitor_category_id = ValueColumn::Capacitance.category_id(pool).await?;
+
+    Ok(SearchConstraints {
+        category_id: capacitor_category_id,
+        column: ValueColumn::Capacitance,
+        nominal: Unit::Farad.stored(quantity.value),
+        min_value: Unit::Farad.stored(min_value),
+        max_value: Unit::Farad.stored(max_value),
+        packages: allowed_package_aliases(
+            &request.package,
+            &request.packages,
+            &request.max_package_size,
+        ),
+        dielectrics: dielectric_names(&request.dielectrics),
+        min_voltage: request.min_voltage,
+        max_voltage: request.max_voltage,
+        min_stock: request.order.required_stock(state.stock_safety_margin)?,
+        basic_only: request.basic_only,
+        preferred_parts: state.preferred_parts(&request.preferred_parts),
+        ..Default::default()
+    })
+}
+
+/// Validate the value, tolerance and filter fields of a capacitor request
+fn validate_request(payload: &CapacitorRequest) -> Result<(), String> {
+    // validate the value, unit and tolerance, given as a value string or as separate fields
+    payload.quantity()?;
+    // validate that max_package_size is a known chip size
+    if payload
+        .max_package_size
+        .as_deref()
+        .is_some_and(|size| chip_size(size).is_none())
+    {
+        return Err("max_package_size must be an imperial chip size code such as 0603".to_string());
+    }
+
+    // validate that the voltage rating bounds are positive and in order
+    if payload.min_voltage.is_some_and(|voltage| voltage < 0.0)
+        || payload.max_voltage.is_some_and(|voltage| voltage < 0.0)
+    {
+        return Err("min_voltage and max_voltage must be positive".to_string());
+    }
+    if let (Some(min_voltage), Some(max_voltage)) = (payload.min_voltage, payload.max_voltage) {
+        if min_voltage > max_voltage {
+            return Err("min_voltage must be smaller than or equal to max_voltage".to_string());
         }
     }
+    // validate the stock requirements
+    payload.order.validate()?;
+    Ok(())
 }
 
-pub fn get_capacitor_value(request_value: f64, request_unit: CapacitorUnit) -> f64 {
-    match request_unit {
-        CapacitorUnit::PicoFarad => return request_value * 1.0,
-        CapacitorUnit::NanoFarad => return request_value * 1e3,
-        CapacitorUnit::MicroFarad => return request_value * 1e6,
-        CapacitorUnit::MilliFarad => return request_value * 1e9,
-        CapacitorUnit::Farad => return request_value * 1e12,
-        CapacitorUnit::KiloFarad => return request_value * 1e15,
-        CapacitorUnit::MegaFarad => return request_value * 1e18,
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jlc::v2::models::capacitor::{CapacitorUnit, Dielectric};
+    use clap::Parser;
+    use dotenv::dotenv;
+
+    use crate::Args;
+
+    #[tokio::test]
+    async fn test_capacitor_voltage_rating() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+        let request = CapacitorRequest {
+            unit: Some(CapacitorUnit::NanoFarad),
+            value: Some(100.0),
+            tolerance_percentage: Some(10.0),
+            min_voltage: Some(25.0),
+            ..Default::default()
+        };
+        let component_vec = find_capacitor(
+            pool,
+            AppState::default(),
+            request,
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components;
+        assert!(!component_vec.is_empty());
+        assert!(component_vec
+            .iter()
+            .all(|component| component.voltage >= Some(25.0)));
+    }
+
+    #[tokio::test]
+    async fn test_capacitor_dielectric() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+        let request = CapacitorRequest {
+            unit: Some(CapacitorUnit::PicoFarad),
+            value: Some(100.0),
+            tolerance_percentage: Some(5.0),
+            dielectrics: Some(vec![Dielectric::C0G]),
+            ..Default::default()
+        };
+        let component_vec = find_capacitor(
+            pool,
+            AppState::default(),
+            request,
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components;
+        assert!(!component_vec.is_empty());
+        assert!(component_vec.iter().all(|component| Dielectric::C0G
+            .names()
+            .contains(&component.dielectric.as_deref().unwrap_or(""))));
+    }
 }
@@ -0,0 +1,29 @@
+use sqlx::PgPool;
+
+use self::category::{Category, Subcategory};
+use crate::jlc::v2::models::*;
+
+pub async fn list_categories(pool: PgPool) -> Result<Vec<Category>, sqlx::Error> {
+    let categories: Vec<Category> = sqlx::query_as!(
+        Category,
+        r#"SELECT c.name as "name!", COUNT(DISTINCT c.id) as "subcategory_count!", COUNT(p.id) as "part_count!", COUNT(p.id) FILTER (WHERE p.basic) as "basic_count!", COUNT(p.id) FILTER (WHERE p.stock > 0) as "in_stock_count!" FROM categories c LEFT JOIN parts p ON p.category_id = c.id GROUP BY c.name ORDER BY c.name"#
+    )
+    .fetch_all(&pool)
+    .await?;
+    Ok(categories)
+}
+
+pub async fn list_subcategories(
+    pool: PgPool,
+    category: Option<String>,
+) -> Result<Vec<Subcategory>, sqlx::Error> {
+    // a NULL category parameter lists the subcategories of every category
+    let subcategories: Vec<Subcategory> = sqlx::query_as!(
+        Subcategory,
+        r#"SELECT c.id as "id!", c.name as "name!", c.subcategory_name as "subcategory_name!", COUNT(p.id) as "part_count!", COUNT(p.id) FILTER (WHERE p.basic) as "basic_count!", COUNT(p.id) FILTER (WHERE p.stock > 0) as "in_stock_count!" FROM categories c LEFT JOIN parts p ON p.category_id = c.id WHERE ($1::text IS NULL OR c.name = $1) GROUP BY c.id ORDER BY c.name, c.subcategory_name"#,
+        category
+    )
+    .fetch_all(&pool)
+    .await?;
+    Ok(subcategories)
+}
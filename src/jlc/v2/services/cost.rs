@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+
+use self::cost::{CostEstimate, CostEstimateRequest, CostLine};
+use self::part::{normalize_lcsc, PartRecord};
+use crate::jlc::error::PartError;
+use crate::jlc::stock::order_quantity;
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::part::find_parts_by_lcsc;
+
+pub async fn estimate_bom_cost(
+    pool: PgPool,
+    request: CostEstimateRequest,
+) -> Result<CostEstimate, PartError> {
+    tracing::info!(
+        "Estimating cost of {} parts for {:?} boards",
+        request.parts.len(),
+        request.boards
+    );
+    let lcsc = request.parts.iter().map(|item| item.lcsc.clone()).collect();
+    let stored_parts = find_parts_by_lcsc(pool.clone(), lcsc).await?;
+    let estimate = cost_lines(&stored_parts.parts, &request)?;
+    let unit_prices = unit_prices(&pool, &estimate.lines).await?;
+    estimate_cost(estimate, &unit_prices)
+}
+
+/// Unit price of every line by LCSC part number, the price tier follows from the order
+/// quantity over all boards and is selected by the `unit_price` function of the database
+async fn unit_prices(
+    pool: &PgPool,
+    lines: &[CostLine],
+) -> Result<HashMap<String, f64>, sqlx::Error> {
+    let lcsc: Vec<String> = lines.iter().map(|line| line.lcsc.clone()).collect();
+    let quantities: Vec<i64> = lines.iter().map(|line| line.quantity).collect();
+    let rows = sqlx::query!(
+        r#"SELECT p.lcsc as "lcsc!", unit_price(p.price_tiers, p.price, line.quantity) as unit_price FROM unnest($1::text[], $2::int8[]) line(lcsc, quantity) JOIN parts p ON p.lcsc = line.lcsc"#,
+        &lcsc,
+        &quantities
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| Some((row.lcsc, row.unit_price?)))
+        .collect())
+}
+
+/// The unique parts of a BOM with their quantity over all boards and assembly fee, every
+/// unique part pays its fee once per order. Quantities too large to represent are an invalid request.
+pub fn cost_lines(
+    stored_parts: &[PartRecord],
+    request: &CostEstimateRequest,
+) -> Result<CostEstimate, PartError> {
+    let boards = request.boards.unwrap_or(1);
+    let fees = request.fees.clone().unwrap_or_default();
+    let mut estimate = CostEstimate {
+        boards,
+        ..Default::default()
+    };
+
+    for item in &request.parts {
+        let lcsc = normalize_lcsc(&item.lcsc);
+        let quantity = order_quantity(Some(item.quantity), Some(boards))?.unwrap_or_default();
+        if let Some(line) = estimate.lines.iter_mut().find(|line| line.lcsc == lcsc) {
+            line.quantity = line.quantity.checked_add(quantity).ok_or_else(|| {
+                PartError::InvalidRequest(format!("The quantity of {} is too large", lcsc))
+            })?;
+            continue;
+        }
+        match stored_parts.iter().find(|part| part.lcsc == lcsc) {
+            Some(part) => estimate.lines.push(CostLine {
+                lcsc,
+                basic: part.basic,
+                unit_price: None,
+                quantity,
+                part_cost: 0.0,
+                fee: if part.basic {
+                    fees.basic_part_fee
+                } else {
+                    fees.extended_part_fee
+                },
+            }),
+            None if !estimate.missing.contains(&lcsc) => estimate.missing.push(lcsc),
+            None => {}
+        }
+    }
+    Ok(estimate)
+}
+
+/// Part cost and assembly fees of the cost lines at their unit prices, a cost too large
+/// to represent is an invalid request
+pub fn estimate_cost(
+    mut estimate: CostEstimate,
+    unit_prices: &HashMap<String, f64>,
+) -> Result<CostEstimate, PartError> {
+    for line in estimate.lines.iter_mut() {
+        line.unit_price = unit_prices.get(&line.lcsc).copied();
+        line.part_cost = line.unit_price.unwrap_or(0.0) * line.quantity as f64;
+    }
+    estimate.extended_parts = estimate.lines.iter().filter(|line| !line.basic).count() as i64;
+    estimate.part_cost = estimate.lines.iter().map(|line| line.part_cost).sum();
+    estimate.fees = estimate.lines.iter().map(|line| line.fee).sum();
+    estimate.total = estimate.part_cost + estimate.fees;
+    estimate.total_per_board = estimate.total / estimate.boards as f64;
+    if !estimate.total.is_finite() {
+        return Err(PartError::InvalidRequest(
+            "The estimated cost is too large".to_string(),
+        ));
+    }
+    Ok(estimate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jlc::v2::models::cost::CostItem;
+    use clap::Parser;
+    use dotenv::dotenv;
+
+    use crate::Args;
+
+    fn stored_part(lcsc: &str, basic: bool) -> PartRecord {
+        PartRecord {
+            lcsc: lcsc.to_string(),
+            category_id: 1,
+            category: None,
+            subcategory: None,
+            mfr: None,
+            package: None,
+            joints: 2,
+            manufacturer: "".to_string(),
+            basic,
+            description: None,
+            datasheet: None,
+            stock: 0,
+            price: None,
+            price_tiers: None,
+            last_update: None,
+            resistance: None,
+            inductance: None,
+            capacitance: None,
+            dielectric: None,
+            current: None,
+            voltage: None,
+            power: None,
+            tolerance: None,
+            saturation_current: None,
+            dcr: None,
+        }
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        let stored_parts = vec![stored_part("C1", true), stored_part("C2", false)];
+        let unit_prices = HashMap::from([("C1".to_string(), 0.01), ("C2".to_string(), 0.1)]);
+        let request = CostEstimateRequest {
+            parts: vec![
+                CostItem {
+                    lcsc: "C1".to_string(),
+                    quantity: 4,
+                },
+                CostItem {
+                    lcsc: "C2".to_string(),
+                    quantity: 1,
+                },
+                CostItem {
+                    lcsc: "c1".to_string(),
+                    quantity: 1,
+                },
+                CostItem {
+                    lcsc: "C3".to_string(),
+                    quantity: 1,
+                },
+            ],
+            boards: Some(10),
+            fees: None,
+        };
+        let lines = cost_lines(&stored_parts, &request).unwrap();
+        let estimate = estimate_cost(lines, &unit_prices).unwrap();
+        assert_eq!(estimate.lines.len(), 2);
+        assert_eq!(estimate.lines[0].quantity, 50);
+        assert_eq!(estimate.missing, vec!["C3".to_string()]);
+        assert_eq!(estimate.extended_parts, 1);
+        assert!((estimate.part_cost - 1.5).abs() < 1e-9);
+        assert!((estimate.total - 4.5).abs() < 1e-9);
+
+        // placements over all boards and the summed quantities are checked for overflow
+        let mut overflowing = request.clone();
+        overflowing.boards = Some(i64::MAX);
+        assert!(cost_lines(&stored_parts, &overflowing).is_err());
+        let mut overflowing = request.clone();
+        overflowing.boards = None;
+        overflowing.parts[0].quantity = i64::MAX;
+        let error = cost_lines(&stored_parts, &overflowing).unwrap_err();
+        assert_eq!(error.code(), "invalid_request");
+
+        // a finite quantity can still overflow the cost
+        let expensive = HashMap::from([("C1".to_string(), f64::MAX)]);
+        let mut overflowing = request;
+        overflowing.parts.truncate(1);
+        let lines = cost_lines(&stored_parts, &overflowing).unwrap();
+        assert!(estimate_cost(lines, &expensive).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_bom_cost_price_tiers() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+
+        // the quantity over all boards of the first placement of each tier pays the price of that tier
+        let lcsc: String =
+            sqlx::query_scalar("SELECT lcsc FROM parts WHERE price_tiers IS NOT NULL LIMIT 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let stored_part = find_parts_by_lcsc(pool.clone(), vec![lcsc.clone()])
+            .await
+            .unwrap()
+            .parts
+            .remove(0);
+        for tier in stored_part.price_tiers.unwrap().iter() {
+            let request = CostEstimateRequest {
+                parts: vec![CostItem {
+                    lcsc: lcsc.clone(),
+                    quantity: 1,
+                }],
+                boards: Some(tier.min_quantity),
+                fees: None,
+            };
+            let estimate = estimate_bom_cost(pool.clone(), request).await.unwrap();
+            assert_eq!(estimate.lines[0].quantity, tier.min_quantity);
+            assert_eq!(estimate.lines[0].unit_price, Some(tier.price));
+        }
+    }
+}
@@ -1,87 +1,143 @@
 use sqlx::PgPool;
 
-use self::inductor::{InductorRequest, InductorUnit};
+use self::inductor::InductorRequest;
+use crate::jlc::constraints::{SearchConstraints, SearchDiagnostics, ValueColumn};
+use crate::jlc::error::PartError;
+use crate::jlc::package::{allowed_package_aliases, chip_size};
+use crate::jlc::quantity::Unit;
 use crate::jlc::v2::models::*;
-
-pub enum Tolerance {
-    Up,
-    Down,
-}
+use crate::jlc::v2::services::candidates::constrained_candidates;
+use crate::AppState;
 
 pub async fn find_inductor(
     pool: PgPool,
+    state: AppState,
     request: InductorRequest,
-) -> Result<Vec<Component>, sqlx::Error> {
-    let jlc_henry_value = get_inductor_value(request.value, request.unit.clone());
-    let jlc_henry_tolerance_up = get_inductor_tolerance(request.clone(), Tolerance::Up);
-    let jlc_henry_tolerance_down = get_inductor_tolerance(request.clone(), Tolerance::Down);
+    range: CandidateRange,
+) -> Result<SearchResults, PartError> {
+    let constraints = search_constraints(&pool, &state, &request).await?;
     tracing::info!(
         "Searching for inductor with value: {} henry, min: {} henry, max: {} henry",
-        jlc_henry_value * 1e-12,
-        jlc_henry_tolerance_down * 1e-12,
-        jlc_henry_tolerance_up * 1e-12
+        Unit::Henry.from_stored(constraints.nominal),
+        Unit::Henry.from_stored(constraints.min_value),
+        Unit::Henry.from_stored(constraints.max_value)
     );
+    let order_quantity = request.order.order_quantity()?;
 
-    let inductor_category_id: (i32,) = sqlx::query_as("SELECT id FROM categories WHERE name = 'Inductors/Coils/Transformers' and subcategory_name = 'Inductors (SMD)'")
-    .fetch_one(&pool).await?;
+    // every matching part is ranked before the best candidates are fetched, the constraints
+    // are the ones the diagnostics count and name the eliminating one when nothing matches
+    let results =
+        constrained_candidates(&pool, &constraints, &request.ranking, order_quantity, range)
+            .await?;
+    Ok(results)
+}
 
-    if request.package.is_some() {
-        let matching_parts: Vec<Component> = sqlx::query_as!(
-            Component,
-            r#"SELECT lcsc as "lcsc!", category_id as "category_id!", mfr as "mfr?", package as "package?", joints as "joints!", manufacturer as "manufacturer!", basic as "basic!", description as "description?", datasheet as "datasheet?", stock as "stock!", price as "price?", dielectric as "dielectric?" FROM parts WHERE category_id = $1 and inductance between $2 and $3 and package = $4 ORDER BY basic DESC LIMIT 100"#,
-            inductor_category_id.0,
-            jlc_henry_tolerance_down,
-            jlc_henry_tolerance_up,
-            request.package.unwrap()
-        ).fetch_all(&pool).await?;
-        return Ok(matching_parts);
-    } else {
-        let matching_parts: Vec<Component> = sqlx::query_as!(
-            Component,
-            r#"SELECT lcsc as "lcsc!", category_id as "category_id!", mfr as "mfr?", package as "package?", joints as "joints!", manufacturer as "manufacturer!", basic as "basic!", description as "description?", datasheet as "datasheet?", stock as "stock!", price as "price?", dielectric as "dielectric?" FROM parts WHERE category_id = $1 and inductance between $2 and $3 ORDER BY basic DESC LIMIT 100"#,
-            inductor_category_id.0,
-            jlc_henry_tolerance_down,
-            jlc_henry_tolerance_up
-        ).fetch_all(&pool).await?;
-        return Ok(matching_parts);
-    }
+/// Count the inductors left after each filter of the request and suggest
+/// the nearest relaxations when none is left
+pub async fn diagnose_inductor(
+    pool: PgPool,
+    state: AppState,
+    request: InductorRequest,
+) -> Result<SearchDiagnostics, PartError> {
+    let constraints = search_constraints(&pool, &state, &request).await?;
+    Ok(constraints.diagnose(&pool).await?)
 }
 
-pub fn get_inductor_tolerance(request: InductorRequest, tolerance: Tolerance) -> f64 {
-    let nominal_value = get_inductor_value(request.value, request.unit);
-    if request.absolute_tolerance.is_some() {
-        let absolute_tolerance = request.absolute_tolerance.unwrap();
-        let absolute_tolerance_unit = request.absolute_tolerance_unit.unwrap();
-        let tolerance_value = get_inductor_value(absolute_tolerance, absolute_tolerance_unit);
-        match tolerance {
-            Tolerance::Up => {
-                return nominal_value + tolerance_value;
-            }
-            Tolerance::Down => {
-                return nominal_value - tolerance_value;
-            }
-        }
-    } else {
-        let tolerance_pct = request.tolerance_percentage.unwrap();
-        match tolerance {
-            Tolerance::Up => {
-                return nominal_value + (nominal_value * (tolerance_pct / 100.0));
-            }
-            Tolerance::Down => {
-                return nominal_value - (nominal_value * (tolerance_pct / 100.0));
-            }
-        }
+/// The filters of an inductor request, values in pH
+async fn search_constraints(
+    pool: &PgPool,
+    state: &AppState,
+    request: &InductorRequest,
+) -> Result<SearchConstraints, PartError> {
+    validate_request(request).map_err(PartError::InvalidRequest)?;
+    let quantity = request.quantity().map_err(PartError::InvalidRequest)?;
+    let (min_value, max_value) = quantity.bounds();
+    let inductor_category_id = ValueColumn::Inductance.category_id(pool).await?;
+
+    Ok(SearchConstraints {
+        category_id: inductor_category_id,
+        column: ValueColumn::Inductance,
+        nominal: Unit::Henry.stored(quantity.value),
+        min_value: Unit::Henry.stored(min_value),
+        max_value: Unit::Henry.stored(max_value),
+        packages: allowed_package_aliases(
+            &request.package,
+            &request.packages,
+            &request.max_package_size,
+        ),
+        min_current: request.min_current,
+        min_saturation_current: request.min_saturation_current,
+        max_dcr: request.max_dcr,
+        min_stock: request.order.required_stock(state.stock_safety_margin)?,
+        basic_only: request.basic_only,
+        preferred_parts: state.preferred_parts(&request.preferred_parts),
+        ..Default::default()
+    })
+}
+
+/// Validate the value, tolerance and filter fields of a inductor request
+fn validate_request(payload: &InductorRequest) -> Result<(), String> {
+    // validate the value, unit and tolerance, given as a value string or as separate fields
+    payload.quantity()?;
+    // validate that max_package_size is a known chip size
+    if payload
+        .max_package_size
+        .as_deref()
+        .is_some_and(|size| chip_size(size).is_none())
+    {
+        return Err("max_package_size must be an imperial chip size code such as 0603".to_string());
+    }
+    // validate the stock requirements
+    payload.order.validate()?;
+    // validate that the current and DC resistance filters are positive
+    if payload.min_current.is_some_and(|current| current < 0.0)
+        || payload
+            .min_saturation_current
+            .is_some_and(|current| current < 0.0)
+        || payload.max_dcr.is_some_and(|dcr| dcr < 0.0)
+    {
+        return Err("min_current, min_saturation_current and max_dcr must be positive".to_string());
     }
+    Ok(())
 }
 
-pub fn get_inductor_value(request_value: f64, request_unit: InductorUnit) -> f64 {
-    match request_unit {
-        InductorUnit::PicoHenry => return request_value * 1.0,
-        InductorUnit::NanoHenry => return request_value * 1e3,
-        InductorUnit::MicroHenry => return request_value * 1e6,
-        InductorUnit::MilliHenry => return request_value * 1e9,
-        InductorUnit::Henry => return request_value * 1e12,
-        InductorUnit::KiloHenry => return request_value * 1e15,
-        InductorUnit::MegaHenry => return request_value * 1e18,
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jlc::v2::models::inductor::InductorUnit;
+    use clap::Parser;
+    use dotenv::dotenv;
+
+    use crate::Args;
+
+    #[tokio::test]
+    async fn test_inductor_currents_and_dcr() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+        let request = InductorRequest {
+            unit: Some(InductorUnit::MicroHenry),
+            value: Some(4.7),
+            tolerance_percentage: Some(20.0),
+            min_current: Some(1.0),
+            min_saturation_current: Some(2.0),
+            max_dcr: Some(0.1),
+            ..Default::default()
+        };
+        let component_vec = find_inductor(
+            pool,
+            AppState::default(),
+            request,
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components;
+        assert!(!component_vec.is_empty());
+        assert!(component_vec.iter().all(|component| {
+            component.current >= Some(1.0)
+                && component.saturation_current >= Some(2.0)
+                && component.dcr.is_some_and(|dcr| dcr <= 0.1)
+        }));
+    }
 }
@@ -0,0 +1,149 @@
+use sqlx::PgPool;
+
+use self::keyword::KeywordSearchRequest;
+use crate::jlc::package::optional_package_aliases;
+use crate::jlc::ranking::Ranking;
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::candidates::CandidateQuery;
+use crate::AppState;
+
+pub async fn search_keywords(
+    pool: PgPool,
+    state: AppState,
+    request: KeywordSearchRequest,
+    range: CandidateRange,
+) -> Result<SearchResults, sqlx::Error> {
+    tracing::info!("Searching parts by keywords: {:?}", request);
+    let packages = optional_package_aliases(&request.package);
+    // a false basic_only accepts extended parts as well, a true one keeps the preferred parts
+    let basic_only = request.basic_only.filter(|basic_only| *basic_only);
+    let preferred_parts = state.preferred_parts(&None);
+
+    // the search document is an indexed text search vector of the description, manufacturer
+    // and part number, the filters are optional and a NULL parameter disables the filter
+    let mut query = CandidateQuery::default();
+    let keywords = query.bind(request.query)?;
+    let category = query.bind(request.category)?;
+    let subcategory = query.bind(request.subcategory)?;
+    let packages = query.bind(packages)?;
+    let basic_only = query.bind(basic_only)?;
+    let preferred = query.bind(preferred_parts.clone())?;
+    query.source = format!(
+        "FROM parts p JOIN categories c ON c.id = p.category_id, websearch_to_tsquery('simple', {keywords}) query WHERE p.search_document @@ query and ({category}::text IS NULL OR c.name = {category}) and ({subcategory}::text IS NULL OR c.subcategory_name = {subcategory}) and ({packages}::text[] IS NULL OR p.package = ANY({packages})) and ({basic_only}::bool IS NULL OR p.basic OR p.lcsc = ANY({preferred}))"
+    );
+    // the best matching parts come first unless a ranking strategy is requested
+    if request.ranking.strategy.is_none() {
+        query.relevance = Some("-ts_rank(p.search_document, query)".to_string());
+    }
+
+    let ranking = Ranking::new(request.ranking.strategy, request.ranking.weights, None)
+        .with_preferred_parts(preferred_parts);
+    query.fetch(&pool, &ranking, range).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use dotenv::dotenv;
+
+    use crate::Args;
+
+    #[tokio::test]
+    async fn test_search_keywords() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+        // every part contains all terms and the best matching parts come first
+        let request = KeywordSearchRequest {
+            query: "thick film 10kΩ".to_string(),
+            ..Default::default()
+        };
+        let component_vec = search_keywords(
+            pool.clone(),
+            AppState::default(),
+            request.clone(),
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components;
+        assert!(!component_vec.is_empty());
+        assert!(component_vec.iter().all(|component| {
+            let text = format!(
+                "{} {} {}",
+                component.description.as_deref().unwrap_or(""),
+                component.manufacturer,
+                component.mfr.as_deref().unwrap_or("")
+            )
+            .to_lowercase();
+            ["thick", "film", "10kω"]
+                .iter()
+                .all(|term| text.contains(term))
+        }));
+        let lcsc: Vec<String> = component_vec
+            .iter()
+            .map(|component| component.lcsc.clone())
+            .collect();
+        let ranks: Vec<(String, f32)> = sqlx::query_as(
+            "SELECT lcsc, ts_rank(search_document, websearch_to_tsquery('simple', $1)) FROM parts WHERE lcsc = ANY($2)",
+        )
+        .bind(&request.query)
+        .bind(&lcsc)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let rank = |lcsc: &String| ranks.iter().find(|(part, _)| part == lcsc).unwrap().1;
+        assert!(lcsc.windows(2).all(|pair| rank(&pair[0]) >= rank(&pair[1])));
+
+        let mut request = KeywordSearchRequest {
+            query: "thick film 0402".to_string(),
+            ..Default::default()
+        };
+        let extended = search_keywords(
+            pool.clone(),
+            AppState::default(),
+            request.clone(),
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components
+        .into_iter()
+        .find(|component| !component.basic)
+        .unwrap();
+
+        request.basic_only = Some(true);
+        let component_vec = search_keywords(
+            pool.clone(),
+            AppState::default(),
+            request.clone(),
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components;
+        assert!(!component_vec.is_empty());
+        assert!(component_vec
+            .iter()
+            .all(|component| component.basic && component.package.as_deref() == Some("0402")));
+
+        // a preferred extended part of the server passes the basic only filter
+        let state = AppState {
+            preferred_parts: vec![extended.lcsc.clone()],
+            ..Default::default()
+        };
+        let component_vec = search_keywords(
+            pool,
+            state,
+            request,
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components;
+        assert!(component_vec
+            .iter()
+            .any(|component| component.lcsc == extended.lcsc));
+    }
+}
@@ -1,3 +1,13 @@
+pub mod bom;
+pub mod candidates;
 pub mod capacitor;
+pub mod category;
+pub mod cost;
 pub mod inductor;
+pub mod keyword;
+pub mod mpn;
+pub mod package;
+pub mod part;
 pub mod resistor;
+pub mod search;
+pub mod standard_value;
@@ -0,0 +1,126 @@
+use sqlx::PgPool;
+
+use self::mpn::MpnSearchRequest;
+use crate::jlc::mpn::{escape_like, fuzzy_pattern, max_edit_distance, normalize_mpn, MpnMatch};
+use crate::jlc::package::optional_package_aliases;
+use crate::jlc::ranking::Ranking;
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::candidates::CandidateQuery;
+use crate::AppState;
+
+pub async fn find_mpn(
+    pool: PgPool,
+    state: AppState,
+    request: MpnSearchRequest,
+    range: CandidateRange,
+) -> Result<SearchResults, sqlx::Error> {
+    tracing::info!("Searching parts by mpn: {:?}", request);
+    let mpn = normalize_mpn(&request.mpn);
+    let match_mode = request.match_mode.unwrap_or_default();
+    let max_distance = max_edit_distance(&mpn);
+    let packages = optional_package_aliases(&request.package);
+
+    // the trigram index of the normalized part number serves every mode: the whole part number,
+    // a LIKE prefix or a regular expression that every part number within the edit distance
+    // matches, the distance itself is only computed for part numbers of a length within it
+    // and a prefix is as far from a part number as the characters it lacks
+    let mut query = CandidateQuery::default();
+    let part_number = query.bind(mpn.clone())?;
+    let (condition, distance) = match match_mode {
+        MpnMatch::Exact => (
+            format!("p.mpn_normalized = {part_number}"),
+            format!("length(p.mpn_normalized) - length({part_number})"),
+        ),
+        MpnMatch::Prefix => {
+            let prefix = query.bind(format!("{}%", escape_like(&mpn)))?;
+            (
+                format!("p.mpn_normalized LIKE {prefix}"),
+                format!("length(p.mpn_normalized) - length({part_number})"),
+            )
+        }
+        MpnMatch::Fuzzy => {
+            let pattern = query.bind(fuzzy_pattern(&mpn, max_distance))?;
+            let max_distance = query.bind(max_distance as i32)?;
+            (
+                format!("p.mpn_normalized ~ {pattern} and CASE WHEN length(p.mpn_normalized) between length({part_number}) - {max_distance} and length({part_number}) + {max_distance} THEN levenshtein_less_equal(p.mpn_normalized, {part_number}, {max_distance}) END <= {max_distance}"),
+                format!("levenshtein_less_equal(p.mpn_normalized, {part_number}, {max_distance})"),
+            )
+        }
+    };
+    let packages = query.bind(packages)?;
+    let basic = query.bind(request.basic)?;
+    query.source = format!(
+        "FROM parts p WHERE {condition} and ({packages}::text[] IS NULL OR p.package = ANY({packages})) and ({basic}::bool IS NULL OR p.basic = {basic})"
+    );
+    // closer part numbers first, the ranking strategy orders equally close parts
+    query.relevance = Some(distance);
+
+    let ranking = Ranking::new(request.ranking.strategy, request.ranking.weights, None)
+        .with_preferred_parts(state.preferred_parts(&None));
+    query.fetch(&pool, &ranking, range).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use dotenv::dotenv;
+
+    use crate::Args;
+
+    #[tokio::test]
+    async fn test_find_mpn() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+        let request = MpnSearchRequest {
+            mpn: "0603waf1002t5e".to_string(),
+            ..Default::default()
+        };
+        let results = find_mpn(
+            pool.clone(),
+            AppState::default(),
+            request,
+            Pagination::default().range(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.total, Some(1));
+        assert_eq!(results.components[0].lcsc, "C25804");
+
+        // one character dropped and one replaced, or the first character replaced,
+        // the closest part number comes first and every match is within the edit distance
+        for mpn in ["0603WAF1O02T5", "1603WAF1002T5E"] {
+            let request = MpnSearchRequest {
+                mpn: mpn.to_string(),
+                match_mode: Some(MpnMatch::Fuzzy),
+                ..Default::default()
+            };
+            let results = find_mpn(
+                pool.clone(),
+                AppState::default(),
+                request,
+                CandidateRange::Best(Pagination::MAX_LIMIT),
+            )
+            .await
+            .unwrap();
+            assert_eq!(results.components[0].lcsc, "C25804");
+            let lcsc: Vec<String> = results
+                .components
+                .iter()
+                .map(|component| component.lcsc.clone())
+                .collect();
+            let distances: Vec<i32> = sqlx::query_scalar(
+                "SELECT levenshtein(mpn_normalized, $1) FROM parts WHERE lcsc = ANY($2)",
+            )
+            .bind(mpn)
+            .bind(&lcsc)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+            assert!(distances
+                .iter()
+                .all(|distance| *distance as usize <= max_edit_distance(mpn)));
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use sqlx::PgPool;
+
+use self::package::{Package, PackageQuery};
+use crate::jlc::package::canonical_package;
+use crate::jlc::v2::models::*;
+
+pub async fn list_packages(pool: PgPool, query: PackageQuery) -> Result<Vec<Package>, sqlx::Error> {
+    // every filter is optional, a NULL parameter disables the filter
+    let stored_packages: Vec<Package> = sqlx::query_as!(
+        Package,
+        r#"SELECT p.package as "package!", COUNT(p.id) as "part_count!", COUNT(p.id) FILTER (WHERE p.basic) as "basic_count!", COUNT(p.id) FILTER (WHERE p.stock > 0) as "in_stock_count!" FROM parts p JOIN categories c ON c.id = p.category_id WHERE p.package IS NOT NULL and ($1::text IS NULL OR c.name = $1) and ($2::text IS NULL OR c.subcategory_name = $2) and ($3::int8 IS NULL OR p.category_id = $3) GROUP BY p.package"#,
+        query.category,
+        query.subcategory,
+        query.category_id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    // merge the spellings of the same package, such as "0402" and "R0402"
+    let mut packages: BTreeMap<String, Package> = BTreeMap::new();
+    for stored_package in stored_packages {
+        let name = canonical_package(&stored_package.package);
+        let package = packages.entry(name.clone()).or_insert(Package {
+            package: name,
+            part_count: 0,
+            basic_count: 0,
+            in_stock_count: 0,
+        });
+        package.part_count += stored_package.part_count;
+        package.basic_count += stored_package.basic_count;
+        package.in_stock_count += stored_package.in_stock_count;
+    }
+
+    // most common packages first
+    let mut packages: Vec<Package> = packages.into_values().collect();
+    packages.sort_by_key(|package| Reverse(package.part_count));
+    Ok(packages)
+}
@@ -0,0 +1,60 @@
+use sqlx::types::Json;
+use sqlx::PgPool;
+
+use self::part::{normalize_lcsc, PartBatchResponse, PartRecord};
+use crate::jlc::price::PriceTier;
+use crate::jlc::v2::models::*;
+
+pub async fn find_part_by_lcsc(
+    pool: PgPool,
+    lcsc: String,
+) -> Result<Option<PartRecord>, sqlx::Error> {
+    let mut parts = find_parts_by_lcsc(pool, vec![lcsc]).await?.parts;
+    Ok(parts.pop())
+}
+
+pub async fn find_parts_by_lcsc(
+    pool: PgPool,
+    lcsc: Vec<String>,
+) -> Result<PartBatchResponse, sqlx::Error> {
+    let lcsc: Vec<String> = lcsc.iter().map(|lcsc| normalize_lcsc(lcsc)).collect();
+
+    let stored_parts: Vec<PartRecord> = sqlx::query_as!(
+        PartRecord,
+        r#"SELECT p.lcsc as "lcsc!", p.category_id as "category_id!", c.name as "category?", c.subcategory_name as "subcategory?", p.mfr as "mfr?", p.package as "package?", p.joints as "joints!", p.manufacturer as "manufacturer!", p.basic as "basic!", p.description as "description?", p.datasheet as "datasheet?", p.stock as "stock!", p.price as "price?", p.price_tiers as "price_tiers?: Json<Vec<PriceTier>>", to_char(p.last_update, 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as "last_update?", p.resistance as "resistance?", p.inductance as "inductance?", p.capacitance as "capacitance?", p.dielectric as "dielectric?", p.current as "current?", p.voltage as "voltage?", p.power as "power?", p.tolerance as "tolerance?", p.saturation_current as "saturation_current?", p.dcr as "dcr?" FROM parts p LEFT JOIN categories c ON c.id = p.category_id WHERE p.lcsc = ANY($1)"#,
+        &lcsc
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    // return the parts in request order and report the unknown ones
+    let mut response = PartBatchResponse::default();
+    for lcsc in lcsc {
+        match stored_parts.iter().find(|part| part.lcsc == lcsc) {
+            Some(part) => response.parts.push(part.clone()),
+            None => response.missing.push(lcsc),
+        }
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use dotenv::dotenv;
+
+    use crate::Args;
+
+    #[tokio::test]
+    async fn test_find_parts_by_lcsc() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+        let lcsc = vec!["c25804".to_string(), "C0".to_string()];
+        let response = find_parts_by_lcsc(pool, lcsc).await.unwrap();
+        assert_eq!(response.parts.len(), 1);
+        assert_eq!(response.parts[0].lcsc, "C25804");
+        assert_eq!(response.missing, vec!["C0".to_string()]);
+    }
+}
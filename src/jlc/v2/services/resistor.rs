@@ -1,89 +1,492 @@
 use sqlx::PgPool;
 
-use self::resistor::{ResistorRequest, ResistorUnit};
+use self::resistor::ResistorRequest;
+use crate::jlc::constraints::{SearchConstraints, SearchDiagnostics, ValueColumn};
+use crate::jlc::error::PartError;
+use crate::jlc::package::{allowed_package_aliases, chip_size};
+use crate::jlc::quantity::Unit;
 use crate::jlc::v2::models::*;
-
-pub enum Tolerance {
-    Up,
-    Down,
-}
+use crate::jlc::v2::services::candidates::constrained_candidates;
+use crate::AppState;
 
 pub async fn find_resistor(
     pool: PgPool,
+    state: AppState,
     request: ResistorRequest,
-) -> Result<Vec<Component>, sqlx::Error> {
-    // value conversion
-    let jlc_ohm_value = get_resistor_value(request.value, request.unit.clone());
-    let jlc_ohm_tolerance_up = get_resistor_tolerance(request.clone(), Tolerance::Up);
-    let jlc_ohm_tolerance_down = get_resistor_tolerance(request.clone(), Tolerance::Down);
+    range: CandidateRange,
+) -> Result<SearchResults, PartError> {
+    let constraints = search_constraints(&pool, &state, &request).await?;
     tracing::info!(
         "Searching for resistor with value: {} ohm, min: {} ohm, max: {} ohm",
-        jlc_ohm_value,
-        jlc_ohm_tolerance_down,
-        jlc_ohm_tolerance_up
+        Unit::Ohm.from_stored(constraints.nominal),
+        Unit::Ohm.from_stored(constraints.min_value),
+        Unit::Ohm.from_stored(constraints.max_value)
     );
+    let order_quantity = request.order.order_quantity()?;
 
-    let resistor_category_id: (i32,) = sqlx::query_as("SELECT id FROM categories WHERE name = 'Resistors' and subcategory_name = 'Chip Resistor - Surface Mount'")
-    .fetch_one(&pool).await?;
-
-    if request.package.is_some() {
-        let matching_parts: Vec<Component> = sqlx::query_as!(
-            Component,
-            r#"SELECT lcsc as "lcsc!", category_id as "category_id!", mfr as "mfr?", package as "package?", joints as "joints!", manufacturer as "manufacturer!", basic as "basic!", description as "description?", datasheet as "datasheet?", stock as "stock!", price as "price?", dielectric as "dielectric?" FROM parts WHERE category_id = $1 and resistance between $2 and $3 and package = $4 ORDER BY basic DESC LIMIT 100"#,
-            resistor_category_id.0,
-            jlc_ohm_tolerance_down,
-            jlc_ohm_tolerance_up,
-            request.package.unwrap()
-        ).fetch_all(&pool).await?;
-        return Ok(matching_parts);
-    } else {
-        let matching_parts: Vec<Component> = sqlx::query_as!(
-            Component,
-            r#"SELECT lcsc as "lcsc!", category_id as "category_id!", mfr as "mfr?", package as "package?", joints as "joints!", manufacturer as "manufacturer!", basic as "basic!", description as "description?", datasheet as "datasheet?", stock as "stock!", price as "price?", dielectric as "dielectric?" FROM parts WHERE category_id = $1 and resistance between $2 and $3 ORDER BY basic DESC LIMIT 100"#,
-            resistor_category_id.0,
-            jlc_ohm_tolerance_down,
-            jlc_ohm_tolerance_up
-        ).fetch_all(&pool).await?;
-        return Ok(matching_parts);
+    // every matching part is ranked before the best candidates are fetched, the constraints
+    // are the ones the diagnostics count and name the eliminating one when nothing matches
+    let mut results =
+        constrained_candidates(&pool, &constraints, &request.ranking, order_quantity, range)
+            .await?;
+
+    // report the E-series value the search used instead of the requested value
+    if let Some(series) = request.e_series {
+        for component in results.components.iter_mut() {
+            component.e_series = Some(series.name().to_string());
+            component.e_series_value = Some(constraints.nominal);
+        }
     }
+    Ok(results)
 }
 
-pub fn get_resistor_tolerance(request: ResistorRequest, tolerance: Tolerance) -> f64 {
-    let nominal_value = get_resistor_value(request.value, request.unit);
-    // check if absolute_tolerance is set or tolerance_percentage is set
-    if request.absolute_tolerance.is_some() {
-        let absolute_tolerance = request.absolute_tolerance.unwrap();
-        let absolute_tolerance_unit = request.absolute_tolerance_unit.unwrap();
-        let tolerance_value = get_resistor_value(absolute_tolerance, absolute_tolerance_unit);
-        match tolerance {
-            Tolerance::Up => {
-                return nominal_value + tolerance_value;
-            }
-            Tolerance::Down => {
-                return nominal_value - tolerance_value;
-            }
+/// Count the resistors left after each filter of the request and suggest
+/// the nearest relaxations when none is left
+pub async fn diagnose_resistor(
+    pool: PgPool,
+    state: AppState,
+    request: ResistorRequest,
+) -> Result<SearchDiagnostics, PartError> {
+    let constraints = search_constraints(&pool, &state, &request).await?;
+    Ok(constraints.diagnose(&pool).await?)
+}
+
+/// The filters of a resistor request, values in ohm
+async fn search_constraints(
+    pool: &PgPool,
+    state: &AppState,
+    request: &ResistorRequest,
+) -> Result<SearchConstraints, PartError> {
+    validate_request(request).map_err(PartError::InvalidRequest)?;
+    let quantity = request.quantity().map_err(PartError::InvalidRequest)?;
+    let (min_value, max_value) = quantity.bounds();
+    let resistor_category_id = ValueColumn::Resistance.category_id(pool).await?;
+
+    Ok(SearchConstraints {
+        category_id: resistor_category_id,
+        column: ValueColumn::Resistance,
+        nominal: Unit::Ohm.stored(quantity.value),
+        min_value: Unit::Ohm.stored(min_value),
+        max_value: Unit::Ohm.stored(max_value),
+        packages: allowed_package_aliases(
+            &request.package,
+            &request.packages,
+            &request.max_package_size,
+        ),
+        min_power: request.min_power,
+        max_part_tolerance: request.max_part_tolerance,
+        min_stock: request.order.required_stock(state.stock_safety_margin)?,
+        basic_only: request.basic_only,
+        preferred_parts: state.preferred_parts(&request.preferred_parts),
+        ..Default::default()
+    })
+}
+
+/// Validate the value, tolerance and filter fields of a resistor request
+fn validate_request(payload: &ResistorRequest) -> Result<(), String> {
+    // validate the value, unit and tolerance, given as a value string or as separate fields
+    payload.quantity()?;
+    // validate that max_package_size is a known chip size
+    if payload
+        .max_package_size
+        .as_deref()
+        .is_some_and(|size| chip_size(size).is_none())
+    {
+        return Err("max_package_size must be an imperial chip size code such as 0603".to_string());
+    }
+    // validate the stock requirements
+    payload.order.validate()?;
+    // validate that the power rating and manufacturer tolerance filters are positive
+    if payload.min_power.is_some_and(|power| power < 0.0) {
+        return Err("min_power must be positive".to_string());
+    }
+    if payload
+        .max_part_tolerance
+        .is_some_and(|tolerance| !(0.0..=100.0).contains(&tolerance))
+    {
+        return Err("max_part_tolerance must be between 0 and 100".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jlc::e_series::ESeries;
+    use std::cmp::Reverse;
+
+    use crate::jlc::ranking::{relative_error, RankingOptions, RankingStrategy, RankingWeights};
+    use crate::jlc::stock::OrderOptions;
+    use crate::jlc::v2::models::resistor::ResistorUnit;
+    use clap::Parser;
+    use dotenv::dotenv;
+
+    use crate::Args;
+
+    fn cheapest_resistor_request(quantity: i64) -> ResistorRequest {
+        ResistorRequest {
+            unit: Some(ResistorUnit::KiloOhm),
+            value: Some(10.0),
+            package: Some("0402".to_string()),
+            tolerance_percentage: Some(5.0),
+            order: OrderOptions {
+                quantity: Some(quantity),
+                ..Default::default()
+            },
+            ranking: RankingOptions {
+                strategy: Some(RankingStrategy::Cheapest),
+                ..Default::default()
+            },
+            ..Default::default()
         }
-    } else {
-        let tolerance_pct = request.tolerance_percentage.unwrap();
-        match tolerance {
-            Tolerance::Up => {
-                return nominal_value + (nominal_value * (tolerance_pct / 100.0));
-            }
-            Tolerance::Down => {
-                return nominal_value - (nominal_value * (tolerance_pct / 100.0));
+    }
+
+    /// Whether the parts are in ascending order of the key
+    fn ascending<K: PartialOrd>(components: &[Component], key: impl Fn(&Component) -> K) -> bool {
+        components
+            .windows(2)
+            .all(|pair| key(&pair[0]) <= key(&pair[1]))
+    }
+
+    /// Sort key of an optional value in ascending order with unknown values last
+    fn none_last(value: Option<f64>) -> (bool, f64) {
+        (value.is_none(), value.unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_resistor_price_tier() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+
+        // every part reports the tier of the ordered quantity and the unit price at that tier
+        // decides the order, parts without a price come last
+        for quantity in [1, 1000] {
+            let component_vec = find_resistor(
+                pool.clone(),
+                AppState::default(),
+                cheapest_resistor_request(quantity),
+                CandidateRange::Best(Pagination::MAX_LIMIT),
+            )
+            .await
+            .unwrap()
+            .components;
+            for component in &component_vec {
+                if let Some(tier) = component.price_tier.as_ref() {
+                    assert!(tier.min_quantity <= quantity);
+                    assert_eq!(component.unit_price, Some(tier.price));
+                }
             }
+            assert!(ascending(&component_vec, |component| none_last(
+                component.unit_price
+            )));
         }
     }
-}
 
-pub fn get_resistor_value(request_value: f64, request_unit: ResistorUnit) -> f64 {
-    match request_unit {
-        ResistorUnit::PicoOhm => return request_value * 1e-12,
-        ResistorUnit::NanoOhm => return request_value * 1e-9,
-        ResistorUnit::MicroOhm => return request_value * 1e-6,
-        ResistorUnit::MilliOhm => return request_value * 1e-3,
-        ResistorUnit::KiloOhm => return request_value * 1e3,
-        ResistorUnit::MegaOhm => return request_value * 1e6,
-        ResistorUnit::Ohm => return request_value,
-    };
+    #[tokio::test]
+    async fn test_resistor_ranking() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+
+        // every strategy orders all matching parts by its criterion, the weighted strategy
+        // with a single criterion orders them like the strategy of that criterion
+        let price_only = RankingWeights {
+            basic: 0.0,
+            price: 1.0,
+            nominal: 0.0,
+            stock: 0.0,
+            joints: 0.0,
+        };
+        let stock_only = RankingWeights {
+            price: 0.0,
+            stock: 1.0,
+            ..price_only.clone()
+        };
+        let rankings = [
+            (RankingStrategy::PreferBasic, None),
+            (RankingStrategy::Cheapest, None),
+            (RankingStrategy::ClosestToNominal, None),
+            (RankingStrategy::HighestStock, None),
+            (RankingStrategy::Weighted, Some(price_only)),
+            (RankingStrategy::Weighted, Some(stock_only)),
+        ];
+        for (strategy, weights) in rankings {
+            let mut request = cheapest_resistor_request(1);
+            request.package = None;
+            request.tolerance_percentage = Some(90.0);
+            request.ranking = RankingOptions {
+                strategy: Some(strategy),
+                weights: weights.clone(),
+            };
+            let nominal = Unit::Ohm.stored(request.quantity().unwrap().value);
+            let whole = CandidateRange::Page {
+                limit: Pagination::MAX_LIMIT,
+                offset: 0,
+            };
+            let results = find_resistor(pool.clone(), AppState::default(), request.clone(), whole)
+                .await
+                .unwrap();
+            let components = &results.components;
+
+            // the whole matching set is fetched, so the order is the order of every match
+            let diagnostics = diagnose_resistor(pool.clone(), AppState::default(), request)
+                .await
+                .unwrap();
+            assert_eq!(
+                results.total,
+                diagnostics.stages.last().map(|stage| stage.remaining)
+            );
+            assert_eq!(Some(components.len() as i64), results.total);
+            assert!(components.len() > 1);
+            assert!(components.iter().all(|component| {
+                let expected = relative_error(component.resistance, Some(nominal)).unwrap();
+                (component.relative_error.unwrap() - expected).abs() < 1e-12
+            }));
+
+            let ordered = match (strategy, weights.map(|weights| weights.price > 0.0)) {
+                (RankingStrategy::PreferBasic, _) => ascending(components, |component| {
+                    (
+                        Reverse(component.basic),
+                        none_last(component.relative_error),
+                        Reverse(component.stock),
+                        component.lcsc.clone(),
+                    )
+                }),
+                (RankingStrategy::Cheapest, _) | (RankingStrategy::Weighted, Some(true)) => {
+                    ascending(components, |component| none_last(component.unit_price))
+                }
+                (RankingStrategy::ClosestToNominal, _) => {
+                    ascending(components, |component| none_last(component.relative_error))
+                }
+                (RankingStrategy::HighestStock, _) | (RankingStrategy::Weighted, _) => {
+                    ascending(components, |component| Reverse(component.stock))
+                }
+            };
+            assert!(ordered, "{:?} order", strategy);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resistor_pages() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+
+        // consecutive pages are the consecutive best candidates, each page counts every
+        // matching part and a page past the last part is empty
+        let mut request = cheapest_resistor_request(1);
+        request.package = None;
+        request.tolerance_percentage = Some(90.0);
+        let search =
+            |range| find_resistor(pool.clone(), AppState::default(), request.clone(), range);
+        let best = search(CandidateRange::Best(Pagination::MAX_LIMIT))
+            .await
+            .unwrap();
+        assert_eq!(best.total, None);
+        let total = best.components.len() as i64;
+        assert!(total > 2);
+
+        let mut paged = Vec::new();
+        for offset in (0..total).step_by(2) {
+            let page = search(CandidateRange::Page { limit: 2, offset })
+                .await
+                .unwrap();
+            assert_eq!(page.total, Some(total));
+            paged.extend(page.components);
+        }
+        assert_eq!(paged, best.components);
+
+        let past_end = search(CandidateRange::Page {
+            limit: 2,
+            offset: total,
+        })
+        .await
+        .unwrap();
+        assert_eq!(past_end.total, Some(total));
+        assert!(past_end.components.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resistor_stock() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+
+        // every part covers the ordered quantity plus the stock safety margin
+        let state = AppState::default();
+        for quantity in [1, 1000] {
+            let request = cheapest_resistor_request(quantity);
+            let required_stock = request
+                .order
+                .required_stock(state.stock_safety_margin)
+                .unwrap()
+                .unwrap();
+            let component_vec = find_resistor(
+                pool.clone(),
+                state.clone(),
+                request,
+                CandidateRange::Best(Pagination::MAX_LIMIT),
+            )
+            .await
+            .unwrap()
+            .components;
+            assert!(component_vec
+                .iter()
+                .all(|component| component.stock >= required_stock));
+        }
+
+        // no part covers an order beyond any stock
+        let error = find_resistor(
+            pool,
+            state,
+            cheapest_resistor_request(10_000_000_000),
+            CandidateRange::Best(1),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.code(), "no_part_found");
+        assert_eq!(error.body().constraint.as_deref(), Some("stock"));
+    }
+
+    #[tokio::test]
+    async fn test_resistor_basic_only() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+
+        let mut request = cheapest_resistor_request(1);
+        let extended = find_resistor(
+            pool.clone(),
+            AppState::default(),
+            request.clone(),
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components
+        .into_iter()
+        .find(|component| !component.basic)
+        .unwrap();
+
+        request.basic_only = Some(true);
+        let component_vec = find_resistor(
+            pool.clone(),
+            AppState::default(),
+            request.clone(),
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components;
+        assert!(component_vec.iter().all(|component| component.basic));
+
+        // a preferred extended part of the server passes the basic only filter
+        let state = AppState {
+            preferred_parts: vec![extended.lcsc.clone()],
+            ..Default::default()
+        };
+        let component_vec = find_resistor(
+            pool,
+            state,
+            request,
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components;
+        assert!(component_vec
+            .iter()
+            .all(|component| component.basic || component.lcsc == extended.lcsc));
+        assert!(component_vec
+            .iter()
+            .any(|component| component.lcsc == extended.lcsc));
+    }
+
+    #[tokio::test]
+    async fn test_resistor_power_and_part_tolerance() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+
+        let mut request = cheapest_resistor_request(1);
+        request.package = None;
+        request.min_power = Some(0.1);
+        request.max_part_tolerance = Some(1.0);
+        let component_vec = find_resistor(
+            pool,
+            AppState::default(),
+            request,
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components;
+        assert!(!component_vec.is_empty());
+        assert!(component_vec
+            .iter()
+            .all(|component| component.power >= Some(0.1)
+                && component
+                    .tolerance
+                    .is_some_and(|tolerance| tolerance <= 1.0)));
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_resistor() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+
+        // each stage keeps a subset of the parts before it, and the stock stage eliminates
+        // the parts of an order beyond any stock
+        let request = cheapest_resistor_request(10_000_000_000);
+        let diagnostics = diagnose_resistor(pool, AppState::default(), request)
+            .await
+            .unwrap();
+        let stages = &diagnostics.stages;
+        assert_eq!(stages[0].constraint, "category");
+        assert!(stages
+            .windows(2)
+            .all(|pair| pair[0].remaining >= pair[1].remaining));
+        let stock = stages
+            .iter()
+            .position(|stage| stage.constraint == "stock")
+            .unwrap();
+        assert!(stages[stock - 1].remaining > 0);
+        assert_eq!(stages[stock].remaining, 0);
+        assert_eq!(diagnostics.eliminating_constraint.as_deref(), Some("stock"));
+    }
+
+    #[tokio::test]
+    async fn test_resistor_e_series() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+
+        // the closest E24 value to 9.8k is 10k, every part is within 1% of it
+        let mut request = cheapest_resistor_request(1);
+        request.value = Some(9.8);
+        request.tolerance_percentage = Some(1.0);
+        request.ranking.strategy = None;
+        request.e_series = Some(ESeries::E24);
+        let component_vec = find_resistor(
+            pool,
+            AppState::default(),
+            request,
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components;
+        assert!(component_vec.iter().all(|component| {
+            component.e_series.as_deref() == Some("E24")
+                && component.e_series_value == Some(10_000.0)
+                && component
+                    .resistance
+                    .is_some_and(|resistance| (9_900.0..=10_100.0).contains(&resistance))
+                && component
+                    .relative_error
+                    .is_some_and(|relative_error| relative_error <= 0.01)
+        }));
+    }
 }
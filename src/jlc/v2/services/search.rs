@@ -0,0 +1,118 @@
+use sqlx::PgPool;
+
+use self::search::PartSearchRequest;
+use crate::jlc::error::PartError;
+use crate::jlc::package::optional_package_aliases;
+use crate::jlc::ranking::Ranking;
+use crate::jlc::v2::models::*;
+use crate::jlc::v2::services::candidates::CandidateQuery;
+use crate::AppState;
+
+pub async fn search_parts(
+    pool: PgPool,
+    state: AppState,
+    request: PartSearchRequest,
+    range: CandidateRange,
+) -> Result<SearchResults, PartError> {
+    tracing::info!("Searching parts: {:?}", request);
+    let packages = optional_package_aliases(&request.package);
+
+    let order_quantity = request.order.order_quantity()?;
+    let min_stock = request.order.required_stock(state.stock_safety_margin)?;
+
+    // every filter is optional, a NULL parameter disables the filter
+    // and a package matches any of its aliases, the price is the unit price at the ordered quantity
+    let mut query = CandidateQuery {
+        order_quantity,
+        ..Default::default()
+    };
+    let category = query.bind(request.category)?;
+    let subcategory = query.bind(request.subcategory)?;
+    let packages = query.bind(packages)?;
+    let basic = query.bind(request.basic)?;
+    let min_stock = query.bind(min_stock)?;
+    let max_price = query.bind(request.max_price)?;
+    let quantity = query.bind(order_quantity)?;
+    let manufacturer = query.bind(request.manufacturer)?;
+    let min_voltage = query.bind(request.min_voltage)?;
+    let max_voltage = query.bind(request.max_voltage)?;
+    let min_current = query.bind(request.min_current)?;
+    let max_current = query.bind(request.max_current)?;
+    let category_id = query.bind(request.category_id)?;
+    query.source = format!(
+        "FROM parts p JOIN categories c ON c.id = p.category_id WHERE ({category}::text IS NULL OR c.name = {category}) and ({subcategory}::text IS NULL OR c.subcategory_name = {subcategory}) and ({packages}::text[] IS NULL OR p.package = ANY({packages})) and ({basic}::bool IS NULL OR p.basic = {basic}) and ({min_stock}::int8 IS NULL OR p.stock >= {min_stock}) and ({max_price}::float8 IS NULL OR unit_price(p.price_tiers, p.price, {quantity}) <= {max_price}) and ({manufacturer}::text IS NULL OR p.manufacturer ILIKE {manufacturer}) and ({min_voltage}::float8 IS NULL OR p.voltage >= {min_voltage}) and ({max_voltage}::float8 IS NULL OR p.voltage <= {max_voltage}) and ({min_current}::float8 IS NULL OR p.current >= {min_current}) and ({max_current}::float8 IS NULL OR p.current <= {max_current}) and ({category_id}::int8 IS NULL OR p.category_id = {category_id})"
+    );
+
+    let ranking = Ranking::new(request.ranking.strategy, request.ranking.weights, None)
+        .with_preferred_parts(state.preferred_parts(&None));
+    Ok(query.fetch(&pool, &ranking, range).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jlc::stock::OrderOptions;
+    use clap::Parser;
+    use dotenv::dotenv;
+
+    use crate::Args;
+
+    #[tokio::test]
+    async fn test_search_parts() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+        let request = PartSearchRequest {
+            category: Some("Diodes".to_string()),
+            min_voltage: Some(30.0),
+            min_current: Some(1.0),
+            ..Default::default()
+        };
+        let component_vec = search_parts(
+            pool,
+            AppState::default(),
+            request,
+            CandidateRange::Best(Pagination::MAX_LIMIT),
+        )
+        .await
+        .unwrap()
+        .components;
+        assert!(!component_vec.is_empty());
+        assert!(component_vec
+            .iter()
+            .all(|component| component.voltage >= Some(30.0) && component.current >= Some(1.0)));
+    }
+
+    #[tokio::test]
+    async fn test_search_max_price() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+
+        // the maximum price applies to the tier price of the ordered quantity,
+        // the same price the ranking and the response report
+        for quantity in [1, 1000] {
+            let request = PartSearchRequest {
+                category: Some("Resistors".to_string()),
+                max_price: Some(0.0005),
+                order: OrderOptions {
+                    quantity: Some(quantity),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let component_vec = search_parts(
+                pool.clone(),
+                AppState::default(),
+                request,
+                CandidateRange::Best(Pagination::MAX_LIMIT),
+            )
+            .await
+            .unwrap()
+            .components;
+            assert!(component_vec
+                .iter()
+                .all(|component| component.unit_price.is_some_and(|price| price <= 0.0005)));
+        }
+    }
+}
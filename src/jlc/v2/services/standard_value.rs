@@ -0,0 +1,108 @@
+use sqlx::PgPool;
+
+use self::standard_value::{StandardValue, StandardValueQuery, StandardValues};
+use crate::jlc::constraints::ValueColumn;
+use crate::jlc::error::PartError;
+use crate::jlc::package::package_aliases;
+use crate::jlc::quantity::{parse_quantity, Unit};
+use crate::jlc::v2::models::*;
+
+/// List the values of a series in a range that are stocked in a package
+pub async fn list_standard_values(
+    pool: PgPool,
+    query: StandardValueQuery,
+) -> Result<StandardValues, PartError> {
+    let unit = Unit::of_part_type(&query.part_type)
+        .ok_or_else(|| PartError::UnsupportedPartType(query.part_type.clone()))?;
+    let column = ValueColumn::of_unit(unit);
+    let min_value = stored_bound(&query.min_value, "min_value", unit)?;
+    let max_value = stored_bound(&query.max_value, "max_value", unit)?;
+    if min_value <= 0.0 || min_value > max_value {
+        return Err(PartError::InvalidRequest(
+            "min_value must be positive and at most max_value".to_string(),
+        ));
+    }
+
+    // stored values can be off by a rounding error, so values are matched with a relative margin
+    let values = query.series.values_between(min_value, max_value);
+    let statement = format!(
+        r#"SELECT v.value as value, COUNT(p.id) as part_count, COUNT(p.id) FILTER (WHERE p.basic) as basic_count, COALESCE(SUM(p.stock), 0)::int8 as stock FROM unnest($1::float8[]) v(value) JOIN parts p ON abs(p.{column} - v.value) <= v.value * 1e-9 WHERE p.category_id = $2 and p.package = ANY($3) and p.stock >= $4 GROUP BY v.value ORDER BY v.value"#,
+        column = column.name()
+    );
+    let values: Vec<StandardValue> = sqlx::query_as(&statement)
+        .bind(&values)
+        .bind(column.category_id(&pool).await?)
+        .bind(package_aliases(&query.package))
+        .bind(query.min_stock.unwrap_or(1))
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(StandardValues {
+        series: query.series,
+        package: query.package,
+        values,
+    })
+}
+
+/// A bound of the range in the unit of the parts table, a bare number is in the SI unit
+fn stored_bound(value: &str, field: &str, unit: Unit) -> Result<f64, PartError> {
+    let quantity = parse_quantity(value).map_err(PartError::InvalidRequest)?;
+    if quantity.unit.is_some_and(|parsed| parsed != unit) || quantity.tolerance.is_some() {
+        return Err(PartError::InvalidRequest(format!(
+            "{} must be a value in {}",
+            field,
+            unit.name()
+        )));
+    }
+    Ok(unit.stored(quantity.value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jlc::e_series::ESeries;
+    use clap::Parser;
+    use dotenv::dotenv;
+
+    use crate::Args;
+
+    fn resistor_query(series: ESeries) -> StandardValueQuery {
+        StandardValueQuery {
+            part_type: "resistor".to_string(),
+            series,
+            min_value: "1k".to_string(),
+            max_value: "10kΩ".to_string(),
+            package: "0402".to_string(),
+            min_stock: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_standard_values() {
+        dotenv().ok();
+        let args = Args::parse();
+        let pool = PgPool::connect(args.database_url.as_str()).await.unwrap();
+
+        // every value is a stocked value of the series in the range, in ascending order
+        for series in [ESeries::E24, ESeries::E96] {
+            let standard_values = list_standard_values(pool.clone(), resistor_query(series))
+                .await
+                .unwrap();
+            let series_values = series.values_between(1000.0, 10_000.0);
+            assert!(standard_values.values.iter().all(|value| {
+                series_values.contains(&value.value)
+                    && value.part_count >= value.basic_count
+                    && value.stock >= value.part_count
+            }));
+            assert!(standard_values
+                .values
+                .windows(2)
+                .all(|pair| pair[0].value < pair[1].value));
+        }
+
+        let mut query = resistor_query(ESeries::E24);
+        query.max_value = "10nF".to_string();
+        let error = list_standard_values(pool, query).await.unwrap_err();
+        assert_eq!(error.code(), "invalid_request");
+    }
+}
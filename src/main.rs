@@ -10,6 +10,8 @@ use utoipauto::utoipauto;
 
 pub mod jlc;
 
+use jlc::v2::models::part::normalize_lcsc;
+
 /// Simple program to greet a person
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -17,6 +19,25 @@ pub struct Args {
     /// DB_URI
     #[arg(short, long, env)]
     database_url: String,
+    /// Fraction of stock required on top of the ordered quantity, 0.1 is 10%
+    #[arg(long, env, default_value_t = AppState::DEFAULT_STOCK_SAFETY_MARGIN, value_parser = parse_stock_safety_margin)]
+    stock_safety_margin: f64,
+    /// Comma separated LCSC part numbers of extended parts that are ranked as basic parts
+    #[arg(long, env, value_delimiter = ',')]
+    preferred_parts: Vec<String>,
+}
+
+/// Parse the stock safety margin, a finite fraction that is not negative
+fn parse_stock_safety_margin(margin: &str) -> Result<f64, String> {
+    let margin: f64 = margin
+        .parse()
+        .map_err(|error| format!("invalid stock safety margin: {error}"))?;
+    if !margin.is_finite() || margin < 0.0 {
+        return Err(format!(
+            "stock safety margin must be finite and not negative, got {margin}"
+        ));
+    }
+    Ok(margin)
 }
 
 #[utoipauto]
@@ -24,8 +45,35 @@ pub struct Args {
 #[openapi(info(title = "JLCPCB Part Selector API", version = "1.0.0"))]
 pub struct ApiDoc;
 
-#[derive(Clone)]
-pub struct AppState {}
+#[derive(Clone, Debug)]
+pub struct AppState {
+    /// Fraction of stock required on top of the ordered quantity, 0.1 is 10%
+    pub stock_safety_margin: f64,
+    /// LCSC part numbers of extended parts that are already loaded and ranked as basic parts
+    pub preferred_parts: Vec<String>,
+}
+
+impl AppState {
+    pub const DEFAULT_STOCK_SAFETY_MARGIN: f64 = 0.1;
+
+    /// The server wide preferred parts combined with the preferred parts of a request
+    pub fn preferred_parts(&self, requested: &Option<Vec<String>>) -> Vec<String> {
+        self.preferred_parts
+            .iter()
+            .chain(requested.iter().flatten())
+            .map(|lcsc| normalize_lcsc(lcsc))
+            .collect()
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        AppState {
+            stock_safety_margin: Self::DEFAULT_STOCK_SAFETY_MARGIN,
+            preferred_parts: Vec::new(),
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() {
@@ -44,7 +92,10 @@ async fn main() {
         .with(EnvFilter::new(_filter))
         .init();
 
-    let app_state = AppState {};
+    let app_state = AppState {
+        stock_safety_margin: args.stock_safety_margin,
+        preferred_parts: args.preferred_parts,
+    };
 
     // set up connection pool
     let pool_extension = PgPoolOptions::new()
@@ -53,6 +104,12 @@ async fn main() {
         .await
         .expect("unable to open db connection");
 
+    // add the columns, indexes and functions of `migrations/` to the scraped tables
+    sqlx::migrate!()
+        .run(&pool_extension)
+        .await
+        .expect("unable to run database migrations");
+
     // build our application with a route
     let app = Router::new()
         .merge(SwaggerUi::new("/docs").url("/docs/openapi.json", ApiDoc::openapi()))